@@ -0,0 +1,6 @@
+pub mod base;
+pub mod context;
+pub mod operators;
+
+pub use base::*;
+pub use context::*;
@@ -0,0 +1,241 @@
+use lisp::{Expression, ExpressionKind, Literal, Operator};
+use vm::bytecode::{Opcode, Value};
+use vm::vm::code::CodeVector;
+
+use crate::codegen::context::{Chunk, CodegenContext};
+use crate::codegen::operators::generate_operator;
+
+#[derive(Debug)]
+pub enum CodegenError {
+    BadArguments(String),
+    NameNotFound(String),
+    UnsupportedExpression(Expression),
+    UnsupportedLiteral(Literal),
+    UnsupportedOperator(Operator),
+    /// Arithmetic the VM can't do the way the evaluator does, as it only
+    /// has `i64`s. Holds the name of the builtin.
+    UnsupportedArithmetic(String),
+}
+
+pub type CodegenResult = Result<(), CodegenError>;
+
+fn _generate_literal(literal: &Literal, chunk: &mut Chunk) -> CodegenResult {
+    let value = match literal {
+        Literal::Integer(num) => Value::Literal(*num),
+        Literal::True => Value::Boolean(true),
+        Literal::Nil => Value::Boolean(false),
+        _ => return Err(CodegenError::UnsupportedLiteral(literal.clone())),
+    };
+    chunk.emit(Opcode::Push(value));
+    Ok(())
+}
+
+fn _generate_name(name: &str, chunk: &mut Chunk, context: &CodegenContext) -> CodegenResult {
+    if let Some(index) = chunk.lookup_local(name) {
+        chunk.emit(Opcode::PushLocal(index));
+    } else if let Some(function) = context.lookup_function(name) {
+        chunk.emit(Opcode::Push(Value::Reference(function.chunk_id)));
+    } else {
+        return Err(CodegenError::NameNotFound(name.to_string()));
+    }
+    Ok(())
+}
+
+fn _builtin_opcode(name: &str) -> Option<(Opcode, bool)> {
+    match name {
+        "+" => Some((Opcode::BinaryAdd, true)),
+        "-" => Some((Opcode::BinarySub, true)),
+        "*" => Some((Opcode::BinaryMul, true)),
+        "/" => Some((Opcode::BinaryDiv, true)),
+        "=" => Some((Opcode::Compare, false)),
+        "<" => Some((Opcode::CompareLess, false)),
+        ">" => Some((Opcode::CompareGreater, false)),
+        "<=" => Some((Opcode::CompareLessEq, false)),
+        ">=" => Some((Opcode::CompareGreaterEq, false)),
+        _ => None,
+    }
+}
+
+/// The values of `arguments` when all of them are integer literals.
+fn _literal_integers(arguments: &[Expression]) -> Option<Vec<i64>> {
+    arguments
+        .iter()
+        .map(|argument| match &*argument.kind {
+            ExpressionKind::Literal(Literal::Integer(value)) => Some(*value),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Divides literal integers, when the quotient is a whole number.
+fn _exact_quotient(arguments: &[Expression]) -> Option<i64> {
+    let values = _literal_integers(arguments)?;
+    values[1..].iter().try_fold(values[0], |quotient, divisor| {
+        match quotient.checked_rem(*divisor)? {
+            0 => quotient.checked_div(*divisor),
+            _ => None,
+        }
+    })
+}
+
+/// Whether `+`, `-` or `*` of literal integers overflows an `i64`.
+fn _overflows(name: &str, arguments: &[Expression]) -> bool {
+    let operation: fn(i64, i64) -> Option<i64> = match name {
+        "+" => i64::checked_add,
+        "-" => i64::checked_sub,
+        "*" => i64::checked_mul,
+        _ => return false,
+    };
+    let mut values = match _literal_integers(arguments) {
+        Some(values) if !values.is_empty() => values,
+        _ => return false,
+    };
+    if values.len() == 1 {
+        // `(- x)` negates.
+        values.insert(0, 0);
+    }
+    values[1..]
+        .iter()
+        .try_fold(values[0], |result, value| operation(result, *value))
+        .is_none()
+}
+
+fn _generate_builtin(
+    name: &str,
+    opcode: Opcode,
+    is_variadic: bool,
+    arguments: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    // The evaluator promotes results that overflow an `i64` to bignums,
+    // which the VM doesn't have. Overflows between literals are caught
+    // here, others fail at runtime with `ExecuteError::IntegerOverflow`.
+    if _overflows(name, arguments) {
+        return Err(CodegenError::UnsupportedArithmetic(name.to_string()));
+    }
+
+    // `(- x)` negates, as it does in the evaluator.
+    if let ("-", [argument]) = (name, arguments) {
+        chunk.emit(Opcode::Push(Value::Literal(0)));
+        generate_expression(argument, chunk, context)?;
+        chunk.emit(Opcode::BinarySub);
+        return Ok(());
+    }
+
+    let valid_size = if is_variadic {
+        arguments.len() >= 2
+    } else {
+        arguments.len() == 2
+    };
+    if !valid_size {
+        return Err(CodegenError::BadArguments(name.to_string()));
+    }
+
+    // The evaluator gives a ratio when a division isn't whole, which the VM
+    // can't represent, so only divisions known to be whole are lowered.
+    if name == "/" {
+        let quotient = _exact_quotient(arguments)
+            .ok_or_else(|| CodegenError::UnsupportedArithmetic(name.to_string()))?;
+        chunk.emit(Opcode::Push(Value::Literal(quotient)));
+        return Ok(());
+    }
+
+    generate_expression(&arguments[0], chunk, context)?;
+    for argument in &arguments[1..] {
+        generate_expression(argument, chunk, context)?;
+        chunk.emit(opcode.clone());
+    }
+    Ok(())
+}
+
+fn _generate_arguments(
+    arguments: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    // The callee pops its parameters off the data stack, so the first
+    // argument has to be pushed last to end up as local 0.
+    arguments
+        .iter()
+        .rev()
+        .try_for_each(|argument| generate_expression(argument, chunk, context))
+}
+
+fn _generate_call(
+    name: &str,
+    arguments: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    if let Some(index) = chunk.lookup_local(name) {
+        _generate_arguments(arguments, chunk, context)?;
+        chunk.emit(Opcode::PushLocal(index));
+        chunk.emit(Opcode::Call(arguments.len()));
+        return Ok(());
+    }
+
+    if let Some(function) = context.lookup_function(name) {
+        if function.arguments_size != arguments.len() {
+            return Err(CodegenError::BadArguments(name.to_string()));
+        }
+
+        let chunk_id = function.chunk_id;
+        _generate_arguments(arguments, chunk, context)?;
+        chunk.emit(Opcode::Push(Value::Reference(chunk_id)));
+        chunk.emit(Opcode::Call(arguments.len()));
+        return Ok(());
+    }
+
+    match _builtin_opcode(name) {
+        Some((opcode, is_variadic)) => {
+            _generate_builtin(name, opcode, is_variadic, arguments, chunk, context)
+        }
+        None => Err(CodegenError::NameNotFound(name.to_string())),
+    }
+}
+
+pub fn generate_expression(
+    expr: &Expression,
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    match &*expr.kind {
+        ExpressionKind::Literal(literal) => _generate_literal(literal, chunk),
+        ExpressionKind::Name(name) => _generate_name(name, chunk, context),
+        ExpressionKind::Call(name, arguments) => _generate_call(name, arguments, chunk, context),
+        ExpressionKind::Operator(operator) => generate_operator(operator, chunk, context),
+        _ => Err(CodegenError::UnsupportedExpression(expr.clone())),
+    }
+}
+
+fn _declare_functions(expressions: &[Expression], context: &mut CodegenContext) {
+    for expr in expressions {
+        if let ExpressionKind::Operator(operator) = &*expr.kind {
+            if let Operator::Function(name, parameters, _) = &**operator {
+                let chunk_id = context.reserve_chunk();
+                context.add_function(name, chunk_id, parameters.len());
+            }
+        }
+    }
+}
+
+pub fn generate(expressions: &[Expression]) -> Result<CodeVector, CodegenError> {
+    let mut context = CodegenContext::new();
+    let main_chunk_id = context.reserve_chunk();
+    context.name_chunk(main_chunk_id, "toplevel");
+    let mut chunk = Chunk::new(Vec::new());
+
+    // Top level functions may call each other regardless of definition order.
+    _declare_functions(expressions, &mut context);
+
+    for (index, expr) in expressions.iter().enumerate() {
+        if index > 0 {
+            chunk.emit(Opcode::Pop);
+        }
+        generate_expression(expr, &mut chunk, &mut context)?;
+    }
+
+    context.set_chunk(main_chunk_id, chunk);
+    Ok(context.into_code())
+}
@@ -0,0 +1,115 @@
+use std::collections::HashMap;
+
+use vm::bytecode::{Opcode, Value};
+use vm::vm::code::CodeVector;
+
+pub struct Chunk {
+    pub code: Vec<Opcode>,
+    pub locals: Vec<String>,
+}
+
+pub struct FunctionEntry {
+    pub chunk_id: usize,
+    pub arguments_size: usize,
+}
+
+pub struct CodegenContext {
+    chunks: Vec<Vec<Opcode>>,
+    /// The name of the function each chunk holds, by chunk id.
+    chunk_names: HashMap<usize, String>,
+    functions: HashMap<String, FunctionEntry>,
+}
+
+impl Chunk {
+    pub fn new(locals: Vec<String>) -> Self {
+        Self {
+            code: Vec::new(),
+            locals,
+        }
+    }
+
+    pub fn emit(&mut self, opcode: Opcode) -> usize {
+        self.code.push(opcode);
+        self.code.len() - 1
+    }
+
+    pub fn emit_reference_placeholder(&mut self) -> usize {
+        self.emit(Opcode::Push(Value::Reference(0)))
+    }
+
+    pub fn patch_reference(&mut self, index: usize, target: usize) {
+        self.code[index] = Opcode::Push(Value::Reference(target));
+    }
+
+    pub fn position(&self) -> usize {
+        self.code.len()
+    }
+
+    /// Finds the most recently added local called `name`.
+    pub fn lookup_local(&self, name: &str) -> Option<usize> {
+        self.locals.iter().rposition(|local| local == name)
+    }
+
+    /// Adds a new local slot, shadowing any earlier local with the same name.
+    pub fn add_local(&mut self, name: &str) -> usize {
+        self.locals.push(name.to_string());
+        self.locals.len() - 1
+    }
+
+    /// Drops the locals added since there were `len`, ending their scope.
+    /// Their slots are reused by later locals.
+    pub fn truncate_locals(&mut self, len: usize) {
+        self.locals.truncate(len);
+    }
+}
+
+impl CodegenContext {
+    pub fn new() -> Self {
+        Self {
+            chunks: Vec::new(),
+            chunk_names: HashMap::new(),
+            functions: HashMap::new(),
+        }
+    }
+
+    pub fn reserve_chunk(&mut self) -> usize {
+        self.chunks.push(Vec::new());
+        self.chunks.len() - 1
+    }
+
+    pub fn set_chunk(&mut self, chunk_id: usize, chunk: Chunk) {
+        self.chunks[chunk_id] = chunk.code;
+    }
+
+    pub fn name_chunk(&mut self, chunk_id: usize, name: &str) {
+        self.chunk_names.insert(chunk_id, name.to_string());
+    }
+
+    pub fn add_function(&mut self, name: &str, chunk_id: usize, arguments_size: usize) {
+        self.functions.insert(
+            name.to_string(),
+            FunctionEntry {
+                chunk_id,
+                arguments_size,
+            },
+        );
+    }
+
+    pub fn lookup_function(&self, name: &str) -> Option<&FunctionEntry> {
+        self.functions.get(name)
+    }
+
+    pub fn into_code(self) -> CodeVector {
+        let mut code = CodeVector::new(self.chunks);
+        for (chunk_id, name) in &self.chunk_names {
+            code.set_chunk_name(*chunk_id, name);
+        }
+        code
+    }
+}
+
+impl Default for CodegenContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
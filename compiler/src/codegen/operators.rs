@@ -0,0 +1,311 @@
+use lisp::{Expression, LoopClause, Operator};
+use vm::bytecode::{Opcode, Value};
+
+use crate::codegen::base::{generate_expression, CodegenError, CodegenResult};
+use crate::codegen::context::{Chunk, CodegenContext};
+
+fn generate_progn(
+    expressions: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    if expressions.is_empty() {
+        chunk.emit(Opcode::Push(Value::Boolean(false)));
+        return Ok(());
+    }
+
+    for (index, expr) in expressions.iter().enumerate() {
+        if index > 0 {
+            chunk.emit(Opcode::Pop);
+        }
+        generate_expression(expr, chunk, context)?;
+    }
+    Ok(())
+}
+
+fn generate_if(
+    condition: &Expression,
+    positive_case: &Expression,
+    negative_case_or_none: &Option<Box<Expression>>,
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    generate_expression(condition, chunk, context)?;
+    let negative_case_ref = chunk.emit_reference_placeholder();
+    chunk.emit(Opcode::JumpFalse);
+
+    generate_expression(positive_case, chunk, context)?;
+    let end_ref = chunk.emit_reference_placeholder();
+    chunk.emit(Opcode::Jump);
+
+    chunk.patch_reference(negative_case_ref, chunk.position());
+    match negative_case_or_none {
+        Some(negative_case) => generate_expression(negative_case, chunk, context)?,
+        None => {
+            chunk.emit(Opcode::Push(Value::Boolean(false)));
+        }
+    }
+
+    chunk.patch_reference(end_ref, chunk.position());
+    Ok(())
+}
+
+fn _generate_body(
+    chunk_id: usize,
+    name: &str,
+    parameters: &[String],
+    code: &Expression,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    context.name_chunk(chunk_id, name);
+    let mut body = Chunk::new(parameters.to_vec());
+    generate_expression(code, &mut body, context)?;
+    body.emit(Opcode::Return(1));
+    context.set_chunk(chunk_id, body);
+    Ok(())
+}
+
+fn generate_function(
+    name: &str,
+    parameters: &[String],
+    code: &Expression,
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    let chunk_id = match context.lookup_function(name) {
+        Some(function) if function.arguments_size == parameters.len() => function.chunk_id,
+        _ => context.reserve_chunk(),
+    };
+    context.add_function(name, chunk_id, parameters.len());
+
+    _generate_body(chunk_id, name, parameters, code, context)?;
+    chunk.emit(Opcode::Push(Value::Reference(chunk_id)));
+    Ok(())
+}
+
+fn generate_lambda(
+    parameters: &[String],
+    code: &Expression,
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    let chunk_id = context.reserve_chunk();
+    _generate_body(chunk_id, "lambda", parameters, code, context)?;
+    chunk.emit(Opcode::Push(Value::Reference(chunk_id)));
+    Ok(())
+}
+
+fn generate_setq(
+    name: &str,
+    expr: &Expression,
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    generate_expression(expr, chunk, context)?;
+    let index = match chunk.lookup_local(name) {
+        Some(index) => index,
+        None => chunk.add_local(name),
+    };
+    chunk.emit(Opcode::SetLocal(index));
+    chunk.emit(Opcode::PushLocal(index));
+    Ok(())
+}
+
+/// Emits `body` for its side effects, leaving nothing on the stack.
+fn _generate_statements(
+    body: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    for expr in body {
+        generate_expression(expr, chunk, context)?;
+        chunk.emit(Opcode::Pop);
+    }
+    Ok(())
+}
+
+/// Emits a jump to `end_refs` (patched once the loop's end is known) when
+/// the value on top of the stack is false.
+fn _emit_exit_unless(chunk: &mut Chunk, end_refs: &mut Vec<usize>) {
+    end_refs.push(chunk.emit_reference_placeholder());
+    chunk.emit(Opcode::JumpFalse);
+}
+
+fn _emit_loop_back(start: usize, chunk: &mut Chunk) {
+    chunk.emit(Opcode::Push(Value::Reference(start)));
+    chunk.emit(Opcode::Jump);
+}
+
+fn _emit_increment(index: usize, chunk: &mut Chunk) {
+    chunk.emit(Opcode::PushLocal(index));
+    chunk.emit(Opcode::Push(Value::Literal(1)));
+    chunk.emit(Opcode::BinaryAdd);
+    chunk.emit(Opcode::SetLocal(index));
+}
+
+fn generate_while(
+    condition: &Expression,
+    body: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    let start = chunk.position();
+    let mut end_refs = vec![];
+    generate_expression(condition, chunk, context)?;
+    _emit_exit_unless(chunk, &mut end_refs);
+    _generate_statements(body, chunk, context)?;
+    _emit_loop_back(start, chunk);
+
+    end_refs
+        .into_iter()
+        .for_each(|end_ref| chunk.patch_reference(end_ref, chunk.position()));
+    chunk.emit(Opcode::Push(Value::Boolean(false)));
+    Ok(())
+}
+
+fn generate_dotimes(
+    name: &str,
+    count: &Expression,
+    result: &Option<Box<Expression>>,
+    body: &[Expression],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    generate_expression(count, chunk, context)?;
+    let outer_locals = chunk.locals.len();
+    // The space keeps the hidden local from clashing with a real name.
+    let limit = chunk.add_local(" dotimes limit");
+    chunk.emit(Opcode::SetLocal(limit));
+    let index = chunk.add_local(name);
+    chunk.emit(Opcode::Push(Value::Literal(0)));
+    chunk.emit(Opcode::SetLocal(index));
+
+    let start = chunk.position();
+    let mut end_refs = vec![];
+    chunk.emit(Opcode::PushLocal(index));
+    chunk.emit(Opcode::PushLocal(limit));
+    chunk.emit(Opcode::CompareLess);
+    _emit_exit_unless(chunk, &mut end_refs);
+    _generate_statements(body, chunk, context)?;
+    _emit_increment(index, chunk);
+    _emit_loop_back(start, chunk);
+
+    end_refs
+        .into_iter()
+        .for_each(|end_ref| chunk.patch_reference(end_ref, chunk.position()));
+    match result {
+        Some(result) => generate_expression(result, chunk, context)?,
+        None => {
+            chunk.emit(Opcode::Push(Value::Boolean(false)));
+        }
+    }
+    chunk.truncate_locals(outer_locals);
+    Ok(())
+}
+
+/// Lowers the `loop` clauses that work on numbers: `for ... from/to`,
+/// `while`, `do` and `sum`. The VM has no lists, so `for ... in` and
+/// `collect` are unsupported.
+fn generate_loop(
+    clauses: &[LoopClause],
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    let outer_locals = chunk.locals.len();
+    let mut counters = vec![];
+    let mut limits = vec![];
+    for clause in clauses {
+        if let LoopClause::ForFrom(name, start, end) = clause {
+            generate_expression(start, chunk, context)?;
+            let index = chunk.add_local(name);
+            chunk.emit(Opcode::SetLocal(index));
+            counters.push(index);
+            limits.push(match end {
+                Some(end) => {
+                    generate_expression(end, chunk, context)?;
+                    let limit = chunk.add_local(" loop limit");
+                    chunk.emit(Opcode::SetLocal(limit));
+                    Some(limit)
+                }
+                None => None,
+            });
+        }
+    }
+
+    let sum = chunk.add_local(" loop sum");
+    chunk.emit(Opcode::Push(Value::Literal(0)));
+    chunk.emit(Opcode::SetLocal(sum));
+
+    let start = chunk.position();
+    let mut end_refs = vec![];
+    let mut ranges = counters.iter().zip(limits.iter());
+    for clause in clauses {
+        match clause {
+            LoopClause::ForFrom(..) => {
+                if let Some((index, Some(limit))) = ranges.next() {
+                    chunk.emit(Opcode::PushLocal(*index));
+                    chunk.emit(Opcode::PushLocal(*limit));
+                    chunk.emit(Opcode::CompareLessEq);
+                    _emit_exit_unless(chunk, &mut end_refs);
+                }
+            }
+            LoopClause::While(condition) => {
+                generate_expression(condition, chunk, context)?;
+                _emit_exit_unless(chunk, &mut end_refs);
+            }
+            LoopClause::Do(body) => _generate_statements(body, chunk, context)?,
+            LoopClause::Sum(value) => {
+                chunk.emit(Opcode::PushLocal(sum));
+                generate_expression(value, chunk, context)?;
+                chunk.emit(Opcode::BinaryAdd);
+                chunk.emit(Opcode::SetLocal(sum));
+            }
+            LoopClause::ForIn(..) | LoopClause::Collect(_) => {
+                return Err(CodegenError::UnsupportedOperator(Operator::Loop(clauses.to_vec())))
+            }
+        }
+    }
+    counters
+        .iter()
+        .for_each(|index| _emit_increment(*index, chunk));
+    _emit_loop_back(start, chunk);
+
+    end_refs
+        .into_iter()
+        .for_each(|end_ref| chunk.patch_reference(end_ref, chunk.position()));
+    if clauses.iter().any(|clause| matches!(clause, LoopClause::Sum(_))) {
+        chunk.emit(Opcode::PushLocal(sum));
+    } else {
+        chunk.emit(Opcode::Push(Value::Boolean(false)));
+    }
+    chunk.truncate_locals(outer_locals);
+    Ok(())
+}
+
+pub fn generate_operator(
+    operator: &Operator,
+    chunk: &mut Chunk,
+    context: &mut CodegenContext,
+) -> CodegenResult {
+    match operator {
+        Operator::ProgN(expressions) => generate_progn(expressions, chunk, context),
+        Operator::If(condition, positive_case, negative_case_or_none) => generate_if(
+            condition,
+            positive_case,
+            negative_case_or_none,
+            chunk,
+            context,
+        ),
+        Operator::Function(name, parameters, code) => {
+            generate_function(name, parameters, code, chunk, context)
+        }
+        Operator::Lambda(parameters, code) => generate_lambda(parameters, code, chunk, context),
+        Operator::SetQ(name, expr) => generate_setq(name, expr, chunk, context),
+        Operator::While(condition, body) => generate_while(condition, body, chunk, context),
+        Operator::DoTimes(name, count, result, body) => {
+            generate_dotimes(name, count, result, body, chunk, context)
+        }
+        Operator::Loop(clauses) => generate_loop(clauses, chunk, context),
+        _ => Err(CodegenError::UnsupportedOperator(operator.clone())),
+    }
+}
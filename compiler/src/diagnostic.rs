@@ -0,0 +1,38 @@
+use lisp::Span;
+
+/// Renders `message` followed by the source line `span` starts on, with
+/// carets under the offending text. `origin` names where the source came
+/// from, such as a file path.
+pub fn render(origin: &str, source: &str, span: Span, message: &str) -> String {
+    let line_text = source
+        .lines()
+        .nth(span.line.saturating_sub(1))
+        .unwrap_or_default();
+    let line_number = span.line.to_string();
+    let gutter = " ".repeat(line_number.len());
+
+    // Keep tabs in the padding so the carets line up with the source line.
+    let padding = line_text
+        .chars()
+        .take(span.column.saturating_sub(1))
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+    let available = line_text.chars().count().saturating_sub(padding.chars().count());
+    let width = source
+        .get(span.offset..span.offset + span.length)
+        .map(|text| text.chars().take_while(|c| *c != '\n').count())
+        .unwrap_or_default()
+        .min(available)
+        .max(1);
+
+    format!(
+        "error: {message}\n\
+         {gutter}--> {origin}:{}:{}\n\
+         {gutter} |\n\
+         {line_number} | {line_text}\n\
+         {gutter} | {padding}{}",
+        span.line,
+        span.column,
+        "^".repeat(width),
+    )
+}
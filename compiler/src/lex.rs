@@ -1,21 +1,55 @@
-use lisp::{Atom, Literal, Token};
+use std::fmt;
+
+use lisp::{Atom, BigInt, BigRational, Literal, Span, Token, TokenKind};
 
 #[derive(Debug)]
 pub enum LexError {
-    FloatParsingFailed,
-    InvalidLiteral(char),
-    StringNeverClosed,
-    ParsingEmptyString,
+    NumberParsingFailed(Span),
+    InvalidLiteral(char, Span),
+    StringNeverClosed(Span),
+    /// A backslash in a string followed by a character that is not an escape.
+    InvalidEscape(char, Span),
+    /// A `\u{...}` escape that is malformed or not a Unicode scalar value.
+    InvalidUnicodeEscape(Span),
+    ParsingEmptyString(Span),
 }
 
-type LexResult<'a> = Result<(&'a [char], Token), LexError>;
+type LexResult<'a> = Result<(&'a [char], TokenKind), LexError>;
+
+impl LexError {
+    pub fn span(&self) -> Span {
+        match self {
+            LexError::NumberParsingFailed(span)
+            | LexError::InvalidLiteral(_, span)
+            | LexError::StringNeverClosed(span)
+            | LexError::InvalidEscape(_, span)
+            | LexError::InvalidUnicodeEscape(span)
+            | LexError::ParsingEmptyString(span) => *span,
+        }
+    }
+}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexError::NumberParsingFailed(_) => write!(f, "invalid number"),
+            LexError::InvalidLiteral(c, _) => write!(f, "unexpected character '{}'", c),
+            LexError::StringNeverClosed(_) => write!(f, "string is never closed"),
+            LexError::InvalidEscape(c, _) => {
+                write!(f, "unknown escape sequence '\\{}'", c.escape_default())
+            }
+            LexError::InvalidUnicodeEscape(_) => write!(f, "invalid unicode escape"),
+            LexError::ParsingEmptyString(_) => write!(f, "unexpected end of input"),
+        }
+    }
+}
 
 fn _is_space(c: &char) -> bool {
     c.is_whitespace() || *c == '\n' || *c == '\r'
 }
 
 fn _is_name_start_char(c: &char) -> bool {
-    c.is_alphabetic() || ['+', '-', '/', '*', '=', '<', '>'].contains(&c)
+    c.is_alphabetic() || ['+', '-', '/', '*', '=', '<', '>', '&'].contains(c)
 }
 
 fn _is_name_char(c: &char) -> bool {
@@ -26,7 +60,7 @@ fn _lex_name_string(source: &[char]) -> Result<(&[char], String), LexError> {
     let mut chars = vec![];
     let mut tmp = source;
 
-    while tmp.len() > 0 {
+    while !tmp.is_empty() {
         match tmp.first() {
             Some(c) if _is_name_char(c) => {
                 chars.push(*c);
@@ -41,23 +75,27 @@ fn _lex_name_string(source: &[char]) -> Result<(&[char], String), LexError> {
     Ok((tmp, String::from_iter(chars)))
 }
 
-fn _lex_name(source: &[char]) -> LexResult {
+fn _lex_name(source: &[char]) -> LexResult<'_> {
     let (rest, raw) = _lex_name_string(source)?;
 
     match raw.as_str() {
-        "t" => Ok((rest, Token::Atom(Atom::Literal(Literal::True)))),
-        "nil" => Ok((rest, Token::Atom(Atom::Literal(Literal::Nil)))),
-        name => Ok((rest, Token::Atom(Atom::Name(String::from(name)))))
+        "t" => Ok((rest, TokenKind::Atom(Atom::Literal(Literal::True)))),
+        "nil" => Ok((rest, TokenKind::Atom(Atom::Literal(Literal::Nil)))),
+        name => Ok((rest, TokenKind::Atom(Atom::Name(String::from(name)))))
     }
 }
 
-fn _lex_number(source: &[char]) -> LexResult {
+fn _lex_number(source: &[char], start: Span) -> LexResult<'_> {
     let mut digits = vec![];
     let mut tmp = source;
+    if let Some(sign @ ('+' | '-')) = tmp.first() {
+        digits.push(sign);
+        tmp = &tmp[1..];
+    }
 
-    while tmp.len() > 0 {
+    while !tmp.is_empty() {
         match tmp.first() {
-            Some(digit) if digit.is_numeric() || *digit == '.' => {
+            Some(digit) if digit.is_numeric() || *digit == '.' || *digit == '/' => {
                 digits.push(digit);
                 tmp = &tmp[1..];
             }
@@ -67,53 +105,194 @@ fn _lex_number(source: &[char]) -> LexResult {
         }
     }
 
-    match String::from_iter(digits).parse::<f64>() {
-        Ok(float) => Ok((tmp, Token::Atom(Atom::Literal(Literal::Number(float))))),
-        Err(_) => Err(LexError::FloatParsingFailed)
+    let exponent_length = _exponent_length(tmp);
+    digits.extend(&tmp[..exponent_length]);
+    tmp = &tmp[exponent_length..];
+
+    // Names can start with digits too, like `1+`.
+    if tmp.first().is_some_and(_is_name_char) {
+        return _lex_name(source);
+    }
+
+    let length = digits.len();
+    match parse_number(&String::from_iter(digits)) {
+        Some(literal) => Ok((tmp, TokenKind::Atom(Atom::Literal(literal)))),
+        None => Err(LexError::NumberParsingFailed(Span { length, ..start }))
+    }
+}
+
+/// The length of the exponent at the start of `source`, like `e3` or
+/// `E-4`, or 0 when there is none.
+fn _exponent_length(source: &[char]) -> usize {
+    if !matches!(source.first(), Some('e' | 'E')) {
+        return 0;
+    }
+    let sign = usize::from(matches!(source.get(1), Some('+' | '-')));
+    match source[1 + sign..].iter().take_while(|c| c.is_ascii_digit()).count() {
+        0 => 0,
+        digits => 1 + sign + digits,
+    }
+}
+
+/// Parses the text of a number: an integer, a float with a `.` or an
+/// exponent like `1e3`, or a ratio like `1/3`, each with an optional sign.
+/// Floats too large for an `f64` are rejected.
+pub fn parse_number(raw: &str) -> Option<Literal> {
+    match raw.split_once('/') {
+        Some((numerator, denominator)) => _parse_ratio(numerator, denominator),
+        None if raw.contains(['.', 'e', 'E']) => _parse_float(raw),
+        None => raw.parse::<BigInt>().ok().map(Literal::integer),
+    }
+}
+
+fn _parse_float(raw: &str) -> Option<Literal> {
+    // `f64` also parses words like `inf` and `NaN`, which aren't numbers
+    // here.
+    let is_numeric = raw.chars().all(|c| c.is_ascii_digit() || ".eE+-".contains(c));
+    match raw.parse::<f64>() {
+        Ok(float) if is_numeric && float.is_finite() => Some(Literal::Float(float)),
+        _ => None,
+    }
+}
+
+fn _parse_ratio(numerator: &str, denominator: &str) -> Option<Literal> {
+    let numerator = numerator.parse::<BigInt>().ok()?;
+    let denominator = denominator.parse::<BigInt>().ok()?;
+    match denominator == BigInt::ZERO {
+        true => None,
+        false => Some(Literal::ratio(BigRational::new(numerator, denominator))),
+    }
+}
+
+/// Reads the escape sequence at the start of `source`, returning the
+/// character it stands for and how many characters it takes up. `spans`
+/// holds the span of every character in `source`.
+fn _lex_escape(source: &[char], spans: &[Span], start: Span) -> Result<(char, usize), LexError> {
+    let escaped = match source.get(1) {
+        Some('"') => '"',
+        Some('\\') => '\\',
+        Some('n') => '\n',
+        Some('t') => '\t',
+        Some('r') => '\r',
+        Some('u') => return _lex_unicode_escape(source, spans, start),
+        Some(c) => return Err(LexError::InvalidEscape(*c, spans[0].to(spans[1]))),
+        None => return Err(LexError::StringNeverClosed(start)),
+    };
+    Ok((escaped, 2))
+}
+
+/// Reads a `\u{...}` escape of one to six hex digits.
+fn _lex_unicode_escape(source: &[char], spans: &[Span], start: Span) -> Result<(char, usize), LexError> {
+    match source.get(2) {
+        Some('{') => {}
+        Some(_) => return Err(LexError::InvalidUnicodeEscape(spans[0].to(spans[2]))),
+        None => return Err(LexError::StringNeverClosed(start)),
+    }
+
+    let digits = source[3..].iter().take_while(|c| c.is_ascii_hexdigit()).count();
+    let end = 3 + digits;
+    let span = spans[0].to(spans[end]);
+    match source.get(end) {
+        Some('}') => {}
+        Some(_) => return Err(LexError::InvalidUnicodeEscape(span)),
+        None => return Err(LexError::StringNeverClosed(start)),
+    }
+
+    match digits {
+        1..=6 => u32::from_str_radix(&String::from_iter(&source[3..end]), 16)
+            .ok()
+            .and_then(char::from_u32)
+            .map(|c| (c, end + 1))
+            .ok_or(LexError::InvalidUnicodeEscape(span)),
+        _ => Err(LexError::InvalidUnicodeEscape(span)),
     }
 }
 
-fn _lex_string(source: &[char]) -> LexResult {
+fn _lex_string<'a>(source: &'a [char], spans: &[Span], start: Span) -> LexResult<'a> {
     let mut chars = vec![];
-    let mut tmp = source;
+    let mut index = 0;
 
-    while tmp.len() > 0 {
-        match tmp.first() {
+    loop {
+        match source.get(index) {
             Some('"') => {
-                tmp = &tmp[1..];
+                index += 1;
                 break;
             }
+            Some('\\') => {
+                let (c, length) = _lex_escape(&source[index..], &spans[index..], start)?;
+                chars.push(c);
+                index += length;
+            }
             Some(c) => {
                 chars.push(*c);
-                tmp = &tmp[1..]
+                index += 1;
             }
             None => {
-                return Err(LexError::StringNeverClosed)
+                return Err(LexError::StringNeverClosed(start))
             }
         }
     }
 
     let string = String::from_iter(chars);
     Ok(
-        (tmp, Token::Atom(Atom::Literal(Literal::String(string))))
+        (&source[index..], TokenKind::Atom(Atom::Literal(Literal::String(string))))
     )
 }
 
-fn _lex_one(source: &[char]) -> LexResult {
+/// Lexes a raw string, `#r"..."`, whose contents are taken verbatim with no
+/// escapes. Any number of `#`s may follow the `r`, and then the string only
+/// ends at a `"` followed by as many `#`s, so `#r#"say "hi""#` can hold
+/// quotes. `source` starts after the `#r`.
+fn _lex_raw_string<'a>(source: &'a [char], spans: &[Span], start: Span) -> LexResult<'a> {
+    let hashes = source.iter().take_while(|c| **c == '#').count();
+    match source.get(hashes) {
+        Some('"') => {}
+        Some(c) => return Err(LexError::InvalidLiteral(*c, spans[hashes])),
+        None => return Err(LexError::StringNeverClosed(start)),
+    }
+
+    let contents = &source[hashes + 1..];
+    let length = (0..contents.len())
+        .find(|index| {
+            contents[*index] == '"'
+                && contents[index + 1..].iter().take_while(|c| **c == '#').count() >= hashes
+        })
+        .ok_or(LexError::StringNeverClosed(start))?;
+    let string = String::from_iter(&contents[..length]);
+    Ok((
+        &contents[length + 1 + hashes..],
+        TokenKind::Atom(Atom::Literal(Literal::String(string))),
+    ))
+}
+
+/// Lexes the token at the start of `source`. `spans` holds the span of
+/// every character in `source`, plus one for the end of input.
+fn _lex_one<'a>(source: &'a [char], spans: &[Span]) -> LexResult<'a> {
+    let start = spans[0];
     match source.first() {
-        Some('(') => Ok((&source[1..], Token::OpenParen)),
-        Some(')') => Ok((&source[1..], Token::CloseParen)),
-        Some('"') => _lex_string(&source[1..]),
+        Some('(') => Ok((&source[1..], TokenKind::OpenParen)),
+        Some(')') => Ok((&source[1..], TokenKind::CloseParen)),
+        Some('\'') => Ok((&source[1..], TokenKind::Quote)),
+        Some('`') => Ok((&source[1..], TokenKind::Backtick)),
+        Some(',') => match source.get(1) {
+            Some('@') => Ok((&source[2..], TokenKind::CommaAt)),
+            _ => Ok((&source[1..], TokenKind::Comma)),
+        },
+        Some('"') => _lex_string(&source[1..], &spans[1..], start),
+        Some('#') if source.get(1) == Some(&'r') => _lex_raw_string(&source[2..], &spans[2..], start),
+        Some('+' | '-') if source.get(1).is_some_and(|c| c.is_numeric()) => {
+            _lex_number(source, start)
+        }
         Some(c) if _is_name_start_char(c) => _lex_name(source),
-        Some(c) if c.is_numeric() => _lex_number(source),
-        Some(c) => Err(LexError::InvalidLiteral(*c)),
-        None => Err(LexError::ParsingEmptyString)
+        Some(c) if c.is_numeric() => _lex_number(source, start),
+        Some(c) => Err(LexError::InvalidLiteral(*c, start)),
+        None => Err(LexError::ParsingEmptyString(start))
     }
 }
 
 fn _skip_space(source: &[char]) -> &[char] {
     let mut tmp = source;
-    while tmp.len() > 0 {
+    while !tmp.is_empty() {
         match tmp.first() {
             Some(c) if _is_space(c) => {
                 tmp = &tmp[1..];
@@ -127,17 +306,59 @@ fn _skip_space(source: &[char]) -> &[char] {
     tmp
 }
 
+/// Computes the position of every character in `source`, plus one for the
+/// end of input, so tokens can be mapped back to their spans.
+fn _char_spans(source: &str, source_id: usize) -> Vec<Span> {
+    let mut spans = vec![];
+    let mut line = 1;
+    let mut column = 1;
+
+    for (offset, c) in source.char_indices() {
+        spans.push(Span {
+            source: source_id,
+            offset,
+            length: c.len_utf8(),
+            line,
+            column,
+        });
+        if c == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    spans.push(Span {
+        source: source_id,
+        offset: source.len(),
+        length: 0,
+        line,
+        column,
+    });
+
+    spans
+}
+
 pub fn lex(source: &str) -> Result<Vec<Token>, LexError> {
+    lex_source(source, 0)
+}
+
+/// Lexes `source` with every span marked as coming from `source_id`.
+pub fn lex_source(source: &str, source_id: usize) -> Result<Vec<Token>, LexError> {
     let mut tokens = vec![];
     let chars = source.chars().collect::<Vec<char>>();
+    let char_spans = _char_spans(source, source_id);
     let mut tmp = &chars[..];
 
-    while tmp.len() > 0 {
+    while !tmp.is_empty() {
         tmp = _skip_space(tmp);
-        if tmp.len() > 0 {
-            let (rest, token) = _lex_one(tmp)?;
+        if !tmp.is_empty() {
+            let position = chars.len() - tmp.len();
+            let start = char_spans[position];
+            let (rest, kind) = _lex_one(tmp, &char_spans[position..])?;
+            let end = char_spans[chars.len() - rest.len()];
             tmp = rest;
-            tokens.push(token);
+            tokens.push(Token::new(kind, start.to(Span { length: 0, ..end })));
         }
     }
 
@@ -1,2 +1,4 @@
+pub mod codegen;
+pub mod diagnostic;
 pub mod lex;
 pub mod parse;
@@ -1,52 +1,99 @@
-use lisp::{Atom, Expression, Token};
+use std::fmt;
+
+use lisp::{Atom, Datum, Expression, ExpressionKind, Operator, Span, Token, TokenKind};
 
 use crate::parse::context::ParseContext;
+use crate::parse::operators::parse_datum;
 
 type ParseResult<'a> = (&'a [Token], Expression);
 
 #[derive(Debug)]
 pub enum ParseError {
-    InvalidAtom,
-    ExpressionNotClosed,
+    InvalidAtom(Span),
+    ExpressionNotClosed(Span),
     InvalidToken(Token),
+    /// A clause of a form like `cond` or `case` has the wrong shape. Holds
+    /// the message describing the expected shape.
+    InvalidClause(&'static str, Span),
 }
 
 enum InitialParseResult {
     Expression(Expression),
-    Invoke(String, Vec<Token>),
+    Invoke(String, Vec<Token>, Span),
+}
+
+impl ParseError {
+    pub fn span(&self) -> Span {
+        match self {
+            ParseError::InvalidAtom(span)
+            | ParseError::ExpressionNotClosed(span)
+            | ParseError::InvalidClause(_, span) => *span,
+            ParseError::InvalidToken(token) => token.span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::InvalidAtom(_) => write!(f, "expected an expression"),
+            ParseError::ExpressionNotClosed(_) => write!(f, "expression is never closed"),
+            ParseError::InvalidToken(token) => write!(f, "unexpected {}", token.kind),
+            ParseError::InvalidClause(message, _) => write!(f, "{}", message),
+        }
+    }
+}
+
+/// Returns the span to blame when `tokens` do not have the expected shape:
+/// the first offending token, or the enclosing form when nothing is left.
+pub fn error_span(tokens: &[Token], context: &ParseContext) -> Span {
+    tokens
+        .first()
+        .map(|token| token.span)
+        .unwrap_or(context.form_span)
+}
+
+/// Returns the span covering the tokens consumed from `tokens` up to `rest`.
+pub fn consumed_span(tokens: &[Token], rest: &[Token]) -> Span {
+    let consumed = tokens.len() - rest.len();
+    match (tokens.first(), consumed) {
+        (Some(first), 1..) => first.span.to(tokens[consumed - 1].span),
+        _ => Span::default(),
+    }
 }
 
-pub struct ParseIterator<'a> {
+pub struct ParseIterator<'a, 'c> {
     tokens: &'a [Token],
+    context: &'c mut ParseContext,
     should_stop: bool,
 }
 
-impl<'a> ParseIterator<'a> {
-    pub fn new(tokens: &'a [Token]) -> Self {
+impl<'a, 'c> ParseIterator<'a, 'c> {
+    pub fn new(tokens: &'a [Token], context: &'c mut ParseContext) -> Self {
         Self {
             tokens,
+            context,
             should_stop: false,
         }
     }
 }
 
-impl<'a> Iterator for ParseIterator<'a> {
+impl<'a, 'c> Iterator for ParseIterator<'a, 'c> {
     type Item = Result<Expression, ParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        let mut context = ParseContext::new();
-        if self.should_stop || self.tokens.len() == 0 {
+        if self.should_stop || self.tokens.is_empty() {
             return None;
         }
 
-        match parse_single_expression(self.tokens, &mut context) {
+        match parse_single_expression(self.tokens, self.context) {
             Ok((rest, expr)) => {
                 self.tokens = rest;
-                return Some(Ok(expr));
+                Some(Ok(expr))
             }
             Err(err) => {
                 self.should_stop = true;
-                return Some(Err(err));
+                Some(Err(err))
             }
         }
     }
@@ -55,17 +102,27 @@ impl<'a> Iterator for ParseIterator<'a> {
 fn _get_inner_tokens<'a>(
     source: &'a [Token],
     target: &mut Vec<Token>,
+    open_span: Span,
 ) -> Result<&'a [Token], ParseError> {
     let mut tmp = source;
-    while tmp.len() > 0 {
+    while !tmp.is_empty() {
         match tmp.first() {
-            Some(Token::CloseParen) => {
+            Some(Token {
+                kind: TokenKind::CloseParen,
+                ..
+            }) => {
                 return Ok(&tmp[1..]);
             }
-            Some(Token::OpenParen) => {
-                target.push(Token::OpenParen);
-                tmp = _get_inner_tokens(&tmp[1..], target)?;
-                target.push(Token::CloseParen);
+            Some(
+                open @ Token {
+                    kind: TokenKind::OpenParen,
+                    ..
+                },
+            ) => {
+                target.push(open.clone());
+                let after_inner = _get_inner_tokens(&tmp[1..], target, open.span)?;
+                target.push(tmp[tmp.len() - after_inner.len() - 1].clone());
+                tmp = after_inner;
             }
             Some(token) => {
                 tmp = &tmp[1..];
@@ -76,7 +133,42 @@ fn _get_inner_tokens<'a>(
             }
         }
     }
-    Err(ParseError::ExpressionNotClosed)
+    Err(ParseError::ExpressionNotClosed(open_span))
+}
+
+fn _parse_datums(tokens: &[Token], context: &mut ParseContext) -> Result<Vec<Datum>, ParseError> {
+    let mut datums = vec![];
+    let mut tmp = tokens;
+    while !tmp.is_empty() {
+        let (rest, datum) = parse_datum(tmp, context, false)?;
+        datums.push(datum);
+        tmp = rest;
+    }
+    Ok(datums)
+}
+
+fn _parse_invoke(
+    name: String,
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<ExpressionKind, ParseError> {
+    match context.operators_factories.get(&name) {
+        Some(factory) => {
+            let operator = factory(tokens, context)?;
+            Ok(ExpressionKind::Operator(Box::new(operator)))
+        }
+        None if context.macros.contains(&name) => {
+            let arguments = _parse_datums(tokens, context)?;
+            Ok(ExpressionKind::Operator(Box::new(Operator::MacroCall(
+                name, arguments,
+            ))))
+        }
+        None => {
+            let expressions =
+                parse(tokens, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+            Ok(ExpressionKind::Call(name, expressions))
+        }
+    }
 }
 
 pub fn parse_single_expression<'a>(
@@ -87,48 +179,75 @@ pub fn parse_single_expression<'a>(
 
     match initial_parse_result {
         (rest, InitialParseResult::Expression(expr)) => Ok((rest, expr)),
-        (rest, InitialParseResult::Invoke(name, tokens)) => {
-            match context.operators_factories.get(&name) {
-                Some(factory) => {
-                    let operator = factory(&tokens, context)?;
-                    Ok((rest, Expression::Operator(Box::new(operator))))
-                }
-                None => {
-                    let expressions =
-                        parse(&tokens).collect::<Result<Vec<Expression>, ParseError>>()?;
-                    Ok((rest, Expression::Call(name.clone(), expressions)))
-                }
-            }
+        (rest, InitialParseResult::Invoke(name, tokens, span)) => {
+            let outer_span = std::mem::replace(&mut context.form_span, span);
+            let kind = _parse_invoke(name, &tokens, context);
+            context.form_span = outer_span;
+            Ok((rest, Expression::new(kind?, span)))
         }
     }
 }
 
 fn _initial_parse_expression<'a>(
     tokens: &'a [Token],
-    _context: &mut ParseContext,
+    context: &mut ParseContext,
 ) -> Result<(&'a [Token], InitialParseResult), ParseError> {
-    match tokens {
-        [Token::OpenParen, Token::Atom(Atom::Name(name)), rest @ ..] => {
+    let first = tokens.first().map(|token| &token.kind);
+    let second = tokens.get(1).map(|token| &token.kind);
+    match (first, second) {
+        (Some(TokenKind::OpenParen), Some(TokenKind::Atom(Atom::Name(name)))) => {
             let mut collected_tokens = vec![];
-            let after_invoke = _get_inner_tokens(rest, &mut collected_tokens)?;
+            let after_invoke = _get_inner_tokens(&tokens[2..], &mut collected_tokens, tokens[0].span)?;
             Ok((
                 after_invoke,
-                InitialParseResult::Invoke(name.clone(), collected_tokens),
+                InitialParseResult::Invoke(
+                    name.clone(),
+                    collected_tokens,
+                    consumed_span(tokens, after_invoke),
+                ),
             ))
         }
-        [Token::Atom(Atom::Literal(literal)), rest @ ..] => Ok((
-            rest,
-            InitialParseResult::Expression(Expression::Literal(literal.clone())),
+        (Some(TokenKind::Atom(Atom::Literal(literal))), _) => Ok((
+            &tokens[1..],
+            InitialParseResult::Expression(Expression::new(
+                ExpressionKind::Literal(literal.clone()),
+                tokens[0].span,
+            )),
         )),
-        [Token::Atom(Atom::Name(name)), rest @ ..] => Ok((
-            rest,
-            InitialParseResult::Expression(Expression::Name(name.clone())),
+        (Some(TokenKind::Atom(Atom::Name(name))), _) => Ok((
+            &tokens[1..],
+            InitialParseResult::Expression(Expression::new(
+                ExpressionKind::Name(name.clone()),
+                tokens[0].span,
+            )),
         )),
-        [tok, ..] => Err(ParseError::InvalidToken(tok.clone())),
-        _ => Err(ParseError::InvalidAtom),
+        (Some(TokenKind::Quote), _) => {
+            let (rest, datum) = parse_datum(&tokens[1..], context, false)?;
+            let operator = Operator::Quote(datum);
+            Ok((
+                rest,
+                InitialParseResult::Expression(Expression::new(
+                    ExpressionKind::Operator(Box::new(operator)),
+                    consumed_span(tokens, rest),
+                )),
+            ))
+        }
+        (Some(TokenKind::Backtick), _) => {
+            let (rest, datum) = parse_datum(&tokens[1..], context, true)?;
+            let operator = Operator::Quasiquote(datum);
+            Ok((
+                rest,
+                InitialParseResult::Expression(Expression::new(
+                    ExpressionKind::Operator(Box::new(operator)),
+                    consumed_span(tokens, rest),
+                )),
+            ))
+        }
+        (Some(_), _) => Err(ParseError::InvalidToken(tokens[0].clone())),
+        (None, _) => Err(ParseError::InvalidAtom(context.form_span)),
     }
 }
 
-pub fn parse(tokens: &[Token]) -> ParseIterator {
-    ParseIterator::new(tokens)
+pub fn parse<'a, 'c>(tokens: &'a [Token], context: &'c mut ParseContext) -> ParseIterator<'a, 'c> {
+    ParseIterator::new(tokens, context)
 }
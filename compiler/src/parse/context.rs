@@ -1,12 +1,22 @@
 use std::collections::{HashMap, HashSet};
 
+use lisp::Span;
+
 use crate::parse::operators::OperatorFactory;
 
-use super::operators::{parse_function, parse_if, parse_lambda, parse_progn, parse_setq};
+use super::operators::{
+    parse_and, parse_block, parse_case, parse_catch, parse_cond, parse_dolist, parse_dotimes,
+    parse_ecase, parse_flet, parse_function, parse_handler_case, parse_if, parse_ignore_errors,
+    parse_labels, parse_lambda, parse_let, parse_let_star, parse_loop, parse_macro, parse_or,
+    parse_progn, parse_quasiquote, parse_quote, parse_return_from, parse_setq, parse_throw,
+    parse_unless, parse_unwind_protect, parse_when, parse_while,
+};
 
 pub struct ParseContext {
     pub operators_factories: HashMap<String, OperatorFactory>,
     pub macros: HashSet<String>,
+    /// Span of the form whose operator is currently being parsed.
+    pub form_span: Span,
 }
 
 impl ParseContext {
@@ -17,9 +27,41 @@ impl ParseContext {
                 (String::from("setq"), parse_setq as OperatorFactory),
                 (String::from("if"), parse_if as OperatorFactory),
                 (String::from("defun"), parse_function as OperatorFactory),
+                (String::from("defmacro"), parse_macro as OperatorFactory),
                 (String::from("lambda"), parse_lambda as OperatorFactory),
+                (String::from("quote"), parse_quote as OperatorFactory),
+                (String::from("quasiquote"), parse_quasiquote as OperatorFactory),
+                (String::from("let"), parse_let as OperatorFactory),
+                (String::from("let*"), parse_let_star as OperatorFactory),
+                (String::from("flet"), parse_flet as OperatorFactory),
+                (String::from("labels"), parse_labels as OperatorFactory),
+                (String::from("and"), parse_and as OperatorFactory),
+                (String::from("or"), parse_or as OperatorFactory),
+                (String::from("cond"), parse_cond as OperatorFactory),
+                (String::from("case"), parse_case as OperatorFactory),
+                (String::from("ecase"), parse_ecase as OperatorFactory),
+                (String::from("when"), parse_when as OperatorFactory),
+                (String::from("unless"), parse_unless as OperatorFactory),
+                (String::from("handler-case"), parse_handler_case as OperatorFactory),
+                (String::from("ignore-errors"), parse_ignore_errors as OperatorFactory),
+                (String::from("unwind-protect"), parse_unwind_protect as OperatorFactory),
+                (String::from("block"), parse_block as OperatorFactory),
+                (String::from("return-from"), parse_return_from as OperatorFactory),
+                (String::from("catch"), parse_catch as OperatorFactory),
+                (String::from("throw"), parse_throw as OperatorFactory),
+                (String::from("while"), parse_while as OperatorFactory),
+                (String::from("dotimes"), parse_dotimes as OperatorFactory),
+                (String::from("dolist"), parse_dolist as OperatorFactory),
+                (String::from("loop"), parse_loop as OperatorFactory),
             ]),
             macros: HashSet::new(),
+            form_span: Span::default(),
         }
     }
 }
+
+impl Default for ParseContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -5,10 +5,12 @@ use super::context::ParseContext;
 
 pub mod function;
 pub mod flow;
+pub mod quote;
 pub mod values;
 
 pub use function::*;
 pub use flow::*;
+pub use quote::*;
 pub use values::*;
 
 pub type OperatorFactory = fn(&[Token], &mut ParseContext) -> Result<Operator, ParseError>;
@@ -1,10 +1,14 @@
-use lisp::{Expression, Operator, Token};
+use lisp::{
+    Atom, CaseClause, CondClause, Datum, ErrorHandler, Expression, Literal, LoopClause, Operator, Span,
+    Token, TokenKind,
+};
 
-use crate::parse::base::{parse, parse_single_expression, ParseError};
+use crate::parse::base::{error_span, parse, parse_single_expression, ParseError};
 use crate::parse::context::ParseContext;
+use crate::parse::operators::quote::parse_datum;
 
-pub fn parse_progn(tokens: &[Token], _context: &mut ParseContext) -> Result<Operator, ParseError> {
-    let expressions = parse(tokens).collect::<Result<Vec<Expression>, ParseError>>()?;
+pub fn parse_progn(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let expressions = parse(tokens, context).collect::<Result<Vec<Expression>, ParseError>>()?;
     Ok(Operator::ProgN(expressions))
 }
 
@@ -12,7 +16,7 @@ pub fn parse_if(tokens: &[Token], context: &mut ParseContext) -> Result<Operator
     let (after_condition, condition) = parse_single_expression(tokens, context)?;
     let (after_positive_case, positive_case) = parse_single_expression(after_condition, context)?;
     let negative_case = {
-        if let Some(Token::CloseParen) = after_positive_case.first() {
+        if after_positive_case.is_empty() {
             None
         } else {
             let (_, else_case) = parse_single_expression(after_positive_case, context)?;
@@ -20,9 +24,373 @@ pub fn parse_if(tokens: &[Token], context: &mut ParseContext) -> Result<Operator
         }
     };
 
-    return Ok(Operator::If(
+    Ok(Operator::If(
         Box::new(condition),
         Box::new(positive_case),
         negative_case,
-    ));
+    ))
+}
+
+pub fn parse_and(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let expressions = parse(tokens, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::And(expressions))
+}
+
+pub fn parse_or(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let expressions = parse(tokens, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::Or(expressions))
+}
+
+const COND_CLAUSE: &str = "expected a cond clause like (test body...)";
+const CASE_CLAUSE: &str = "expected a case clause like (keys body...)";
+const OTHERWISE_NOT_LAST: &str = "otherwise must be the last case clause";
+
+/// Parses a `(test body...)` clause of `cond`.
+fn _parse_cond_clause<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], CondClause), ParseError> {
+    match (_kind_at(tokens, 0), _kind_at(tokens, 1)) {
+        (Some(TokenKind::OpenParen), Some(kind)) if *kind != TokenKind::CloseParen => {}
+        _ => return Err(ParseError::InvalidClause(COND_CLAUSE, error_span(tokens, context))),
+    }
+
+    let (after_test, test) = parse_single_expression(&tokens[1..], context)?;
+    let (rest, body) = _parse_until_close(after_test, context, tokens[0].span)?;
+    Ok((rest, (test, body)))
+}
+
+pub fn parse_cond(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let mut clauses = vec![];
+    let mut tmp = tokens;
+    while !tmp.is_empty() {
+        let (rest, clause) = _parse_cond_clause(tmp, context)?;
+        clauses.push(clause);
+        tmp = rest;
+    }
+
+    Ok(Operator::Cond(clauses))
+}
+
+/// Parses a `(keys body...)` clause of `case`, where the keys are a list,
+/// a single key, or `otherwise` (also spelled `t`). `nil` is the empty key
+/// list, so its clause never matches.
+fn _parse_case_clause<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], CaseClause), ParseError> {
+    let (after_keys, keys) = match (_kind_at(tokens, 0), _kind_at(tokens, 1)) {
+        (Some(TokenKind::OpenParen), Some(TokenKind::Atom(Atom::Name(name)))) if name == "otherwise" => {
+            (&tokens[2..], None)
+        }
+        (Some(TokenKind::OpenParen), Some(TokenKind::Atom(Atom::Literal(Literal::True)))) => {
+            (&tokens[2..], None)
+        }
+        (Some(TokenKind::OpenParen), Some(TokenKind::Atom(Atom::Literal(Literal::Nil)))) => {
+            (&tokens[2..], Some(vec![]))
+        }
+        (Some(TokenKind::OpenParen), Some(TokenKind::OpenParen)) => {
+            match parse_datum(&tokens[1..], context, false)? {
+                (rest, Datum::List(keys)) => (rest, Some(keys)),
+                (rest, key) => (rest, Some(vec![key])),
+            }
+        }
+        (Some(TokenKind::OpenParen), Some(kind)) if *kind != TokenKind::CloseParen => {
+            let (rest, key) = parse_datum(&tokens[1..], context, false)?;
+            (rest, Some(vec![key]))
+        }
+        _ => return Err(ParseError::InvalidClause(CASE_CLAUSE, error_span(tokens, context))),
+    };
+
+    let (rest, body) = _parse_until_close(after_keys, context, tokens[0].span)?;
+    Ok((rest, (keys, body)))
+}
+
+fn _parse_case(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<(Box<Expression>, Vec<CaseClause>), ParseError> {
+    let (mut tmp, key) = parse_single_expression(tokens, context)?;
+    let mut clauses: Vec<CaseClause> = vec![];
+    while !tmp.is_empty() {
+        if let Some((None, _)) = clauses.last() {
+            return Err(ParseError::InvalidClause(OTHERWISE_NOT_LAST, tmp[0].span));
+        }
+        let (rest, clause) = _parse_case_clause(tmp, context)?;
+        clauses.push(clause);
+        tmp = rest;
+    }
+
+    Ok((Box::new(key), clauses))
+}
+
+pub fn parse_case(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (key, clauses) = _parse_case(tokens, context)?;
+    Ok(Operator::Case(key, clauses))
+}
+
+pub fn parse_ecase(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (key, clauses) = _parse_case(tokens, context)?;
+    Ok(Operator::ECase(key, clauses))
+}
+
+pub fn parse_when(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (after_condition, condition) = parse_single_expression(tokens, context)?;
+    let body = parse(after_condition, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::When(Box::new(condition), body))
+}
+
+pub fn parse_unless(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (after_condition, condition) = parse_single_expression(tokens, context)?;
+    let body = parse(after_condition, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::Unless(Box::new(condition), body))
+}
+
+/// Parses expressions up to the `)` closing the list opened at `open_span`.
+fn _parse_until_close<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+    open_span: Span,
+) -> Result<(&'a [Token], Vec<Expression>), ParseError> {
+    let mut expressions = vec![];
+    let mut tmp = tokens;
+    loop {
+        match tmp.first().map(|token| &token.kind) {
+            Some(TokenKind::CloseParen) => return Ok((&tmp[1..], expressions)),
+            Some(_) => {
+                let (rest, expr) = parse_single_expression(tmp, context)?;
+                expressions.push(expr);
+                tmp = rest;
+            }
+            None => return Err(ParseError::ExpressionNotClosed(open_span)),
+        }
+    }
+}
+
+fn _kind_at(tokens: &[Token], index: usize) -> Option<&TokenKind> {
+    tokens.get(index).map(|token| &token.kind)
+}
+
+/// Parses a `(type (variable) body...)` clause, where the variable list may
+/// be empty.
+fn _parse_error_handler<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], ErrorHandler), ParseError> {
+    let condition_type = match (_kind_at(tokens, 0), _kind_at(tokens, 1), _kind_at(tokens, 2)) {
+        (
+            Some(TokenKind::OpenParen),
+            Some(TokenKind::Atom(Atom::Name(condition_type))),
+            Some(TokenKind::OpenParen),
+        ) => condition_type.clone(),
+        (Some(TokenKind::OpenParen), ..) => {
+            return Err(ParseError::InvalidAtom(error_span(&tokens[1..], context)))
+        }
+        _ => return Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    };
+
+    let (variable, body_start) = match (_kind_at(tokens, 3), _kind_at(tokens, 4)) {
+        (Some(TokenKind::CloseParen), _) => (None, 4),
+        (Some(TokenKind::Atom(Atom::Name(variable))), Some(TokenKind::CloseParen)) => {
+            (Some(variable.clone()), 5)
+        }
+        _ => return Err(ParseError::InvalidAtom(error_span(&tokens[3..], context))),
+    };
+
+    let (rest, body) = _parse_until_close(&tokens[body_start..], context, tokens[0].span)?;
+    Ok((rest, (condition_type, variable, body)))
+}
+
+pub fn parse_handler_case(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<Operator, ParseError> {
+    let (mut tmp, expr) = parse_single_expression(tokens, context)?;
+    let mut handlers = vec![];
+    while !tmp.is_empty() {
+        let (rest, handler) = _parse_error_handler(tmp, context)?;
+        handlers.push(handler);
+        tmp = rest;
+    }
+
+    Ok(Operator::HandlerCase(Box::new(expr), handlers))
+}
+
+pub fn parse_ignore_errors(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<Operator, ParseError> {
+    let expressions = parse(tokens, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::IgnoreErrors(expressions))
+}
+
+pub fn parse_unwind_protect(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<Operator, ParseError> {
+    let (after_protected, protected) = parse_single_expression(tokens, context)?;
+    let cleanup = parse(after_protected, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::UnwindProtect(Box::new(protected), cleanup))
+}
+
+/// The name of a block, which may be `nil` as for the implicit block of
+/// the iteration forms.
+fn _block_name(tokens: &[Token], context: &ParseContext) -> Result<String, ParseError> {
+    match tokens.first().map(|token| &token.kind) {
+        Some(TokenKind::Atom(Atom::Name(name))) => Ok(name.clone()),
+        Some(TokenKind::Atom(Atom::Literal(Literal::Nil))) => Ok(String::from("nil")),
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    }
+}
+
+pub fn parse_block(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let name = _block_name(tokens, context)?;
+    let body = parse(&tokens[1..], context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::Block(name, body))
+}
+
+pub fn parse_return_from(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<Operator, ParseError> {
+    let name = _block_name(tokens, context)?;
+    if tokens.len() == 1 {
+        return Ok(Operator::ReturnFrom(name, None));
+    }
+
+    match parse_single_expression(&tokens[1..], context)? {
+        ([], value) => Ok(Operator::ReturnFrom(name, Some(Box::new(value)))),
+        ([token, ..], _) => Err(ParseError::InvalidToken(token.clone())),
+    }
+}
+
+pub fn parse_catch(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (after_tag, tag) = parse_single_expression(tokens, context)?;
+    let body = parse(after_tag, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::Catch(Box::new(tag), body))
+}
+
+pub fn parse_throw(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (after_tag, tag) = parse_single_expression(tokens, context)?;
+    match parse_single_expression(after_tag, context)? {
+        ([], value) => Ok(Operator::Throw(Box::new(tag), Box::new(value))),
+        ([token, ..], _) => Err(ParseError::InvalidToken(token.clone())),
+    }
+}
+
+pub fn parse_while(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (after_condition, condition) = parse_single_expression(tokens, context)?;
+    let body = parse(after_condition, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok(Operator::While(Box::new(condition), body))
+}
+
+type IterationSpec = (String, Box<Expression>, Option<Box<Expression>>, Vec<Expression>);
+
+/// Parses the `(name value [result]) body...` shared by `dotimes` and
+/// `dolist`.
+fn _parse_iteration(tokens: &[Token], context: &mut ParseContext) -> Result<IterationSpec, ParseError> {
+    let name = match (_kind_at(tokens, 0), _kind_at(tokens, 1)) {
+        (Some(TokenKind::OpenParen), Some(TokenKind::Atom(Atom::Name(name)))) => name.clone(),
+        (Some(TokenKind::OpenParen), _) => {
+            return Err(ParseError::InvalidAtom(error_span(&tokens[1..], context)))
+        }
+        _ => return Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    };
+
+    let (after_value, value) = parse_single_expression(&tokens[2..], context)?;
+    let (rest, mut spec) = _parse_until_close(after_value, context, tokens[0].span)?;
+    if spec.len() > 1 {
+        return Err(ParseError::InvalidAtom(spec[1].span));
+    }
+
+    let body = parse(rest, context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok((name, Box::new(value), spec.pop().map(Box::new), body))
+}
+
+pub fn parse_dotimes(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (name, count, result, body) = _parse_iteration(tokens, context)?;
+    Ok(Operator::DoTimes(name, count, result, body))
+}
+
+pub fn parse_dolist(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (name, list, result, body) = _parse_iteration(tokens, context)?;
+    Ok(Operator::DoList(name, list, result, body))
+}
+
+fn _loop_keyword(tokens: &[Token]) -> Option<&str> {
+    match _kind_at(tokens, 0) {
+        Some(TokenKind::Atom(Atom::Name(name))) => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+/// Parses `name from start [to end]` or `name in list` after a `for`.
+fn _parse_for_clause<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], LoopClause), ParseError> {
+    let name = match _loop_keyword(tokens) {
+        Some(name) => name.to_string(),
+        None => return Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    };
+
+    let after_name = &tokens[1..];
+    match _loop_keyword(after_name) {
+        Some("in") => {
+            let (rest, list) = parse_single_expression(&after_name[1..], context)?;
+            Ok((rest, LoopClause::ForIn(name, list)))
+        }
+        Some("from") => {
+            let (rest, start) = parse_single_expression(&after_name[1..], context)?;
+            match _loop_keyword(rest) {
+                Some("to") => {
+                    let (rest, end) = parse_single_expression(&rest[1..], context)?;
+                    Ok((rest, LoopClause::ForFrom(name, start, Some(end))))
+                }
+                _ => Ok((rest, LoopClause::ForFrom(name, start, None))),
+            }
+        }
+        _ => Err(ParseError::InvalidAtom(error_span(after_name, context))),
+    }
+}
+
+pub fn parse_loop(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let mut clauses = vec![];
+    let mut tmp = tokens;
+    while !tmp.is_empty() {
+        let (rest, clause) = match _loop_keyword(tmp) {
+            Some("for") => _parse_for_clause(&tmp[1..], context)?,
+            Some("while") => {
+                let (rest, condition) = parse_single_expression(&tmp[1..], context)?;
+                (rest, LoopClause::While(condition))
+            }
+            Some("collect") => {
+                let (rest, value) = parse_single_expression(&tmp[1..], context)?;
+                (rest, LoopClause::Collect(value))
+            }
+            Some("sum") => {
+                let (rest, value) = parse_single_expression(&tmp[1..], context)?;
+                (rest, LoopClause::Sum(value))
+            }
+            Some("do") => {
+                // `do` takes every compound form up to the next keyword.
+                let mut rest = &tmp[1..];
+                let mut body = vec![];
+                while let Some(TokenKind::OpenParen) = _kind_at(rest, 0) {
+                    let (after, expr) = parse_single_expression(rest, context)?;
+                    body.push(expr);
+                    rest = after;
+                }
+                if body.is_empty() {
+                    return Err(ParseError::InvalidAtom(error_span(&tmp[1..], context)));
+                }
+                (rest, LoopClause::Do(body))
+            }
+            _ => return Err(ParseError::InvalidToken(tmp[0].clone())),
+        };
+        clauses.push(clause);
+        tmp = rest;
+    }
+
+    Ok(Operator::Loop(clauses))
 }
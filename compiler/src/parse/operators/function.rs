@@ -1,53 +1,166 @@
-use lisp::{Atom, Operator, Token};
+use lisp::{Atom, Expression, ExpressionKind, LocalFunction, Operator, Token, TokenKind};
 
-use crate::parse::base::{parse_single_expression, ParseError};
+use crate::parse::base::{consumed_span, error_span, parse, parse_single_expression, ParseError};
 use crate::parse::context::ParseContext;
 
+fn _first_kinds(tokens: &[Token]) -> (Option<&TokenKind>, Option<&TokenKind>) {
+    (
+        tokens.first().map(|token| &token.kind),
+        tokens.get(1).map(|token| &token.kind),
+    )
+}
+
+/// Parses the body forms of a function up to the end of `tokens` or a
+/// closing paren, returning the tokens from that paren on. Several forms
+/// make an implicit `progn`.
+fn _parse_body<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], Expression), ParseError> {
+    let mut body = vec![];
+    let mut tmp = tokens;
+    while let Some(token) = tmp.first() {
+        if token.kind == TokenKind::CloseParen {
+            break;
+        }
+        let (rest, expr) = parse_single_expression(tmp, context)?;
+        body.push(expr);
+        tmp = rest;
+    }
+
+    match body.len() {
+        0 => Err(ParseError::InvalidAtom(error_span(tokens, context))),
+        1 => Ok((tmp, body.remove(0))),
+        _ => {
+            let span = consumed_span(tokens, tmp);
+            let kind = ExpressionKind::Operator(Box::new(Operator::ProgN(body)));
+            Ok((tmp, Expression::new(kind, span)))
+        }
+    }
+}
+
 pub fn parse_function(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
-    match tokens {
-        [Token::Atom(Atom::Name(name)), Token::OpenParen, rest @ ..] => {
-            let (after_parameters, parameters) = _parse_parameters(rest)?;
-            let (_, code) = parse_single_expression(after_parameters, context)?;
-            return Ok(Operator::Function(name.clone(), parameters, Box::new(code)))
+    match _first_kinds(tokens) {
+        (Some(TokenKind::Atom(Atom::Name(name))), Some(TokenKind::OpenParen)) => {
+            let (after_parameters, parameters) = _parse_parameters(&tokens[2..], context)?;
+            let (_, code) = _parse_body(after_parameters, context)?;
+            Ok(Operator::Function(name.clone(), parameters, Box::new(code)))
+        }
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context)))
+    }
+}
+
+pub fn parse_macro(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    match _first_kinds(tokens) {
+        (Some(TokenKind::Atom(Atom::Name(name))), Some(TokenKind::OpenParen)) => {
+            let (after_parameters, parameters) = _parse_parameters(&tokens[2..], context)?;
+            context.macros.insert(name.clone());
+            let (_, code) = _parse_body(after_parameters, context)?;
+            Ok(Operator::Macro(name.clone(), parameters, Box::new(code)))
         }
-        _ => Err(ParseError::InvalidAtom)
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context)))
     }
 }
 
 pub fn parse_lambda(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
-    match tokens {
-        [Token::OpenParen, rest @ ..] => {
-            let (after_parameters, parameters) = _parse_parameters(rest)?;
-            let (_, code) = parse_single_expression(after_parameters, context)?;
-            return Ok(Operator::Lambda(parameters, Box::new(code)))
+    match _first_kinds(tokens) {
+        (Some(TokenKind::OpenParen), _) => {
+            let (after_parameters, parameters) = _parse_parameters(&tokens[1..], context)?;
+            let (_, code) = _parse_body(after_parameters, context)?;
+            Ok(Operator::Lambda(parameters, Box::new(code)))
         },
-        _ => Err(ParseError::InvalidAtom)
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context)))
     }
 }
 
-fn _parse_parameters(tokens: &[Token]) -> Result<(&[Token], Vec<String>), ParseError> {
+/// Parses a single `(name (parameters) body)` local function definition.
+fn _parse_local_function<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], LocalFunction), ParseError> {
+    match (_first_kinds(tokens), tokens.get(2).map(|token| &token.kind)) {
+        (
+            (Some(TokenKind::OpenParen), Some(TokenKind::Atom(Atom::Name(name)))),
+            Some(TokenKind::OpenParen),
+        ) => {
+            let (after_parameters, parameters) = _parse_parameters(&tokens[3..], context)?;
+            let (rest, code) = _parse_body(after_parameters, context)?;
+            match rest.first() {
+                Some(Token { kind: TokenKind::CloseParen, .. }) => {
+                    Ok((&rest[1..], (name.clone(), parameters, code)))
+                }
+                Some(token) => Err(ParseError::InvalidToken(token.clone())),
+                None => Err(ParseError::ExpressionNotClosed(tokens[0].span)),
+            }
+        }
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    }
+}
+
+/// Parses the list of definitions that opens an `flet` or `labels`,
+/// followed by the body forms.
+fn _parse_local_functions(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<(Vec<LocalFunction>, Vec<Expression>), ParseError> {
+    match tokens.first().map(|token| &token.kind) {
+        Some(TokenKind::OpenParen) => {}
+        _ => return Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    }
+
+    let mut functions = vec![];
+    let mut tmp = &tokens[1..];
+    loop {
+        match tmp.first().map(|token| &token.kind) {
+            Some(TokenKind::CloseParen) => break,
+            Some(_) => {
+                let (rest, function) = _parse_local_function(tmp, context)?;
+                functions.push(function);
+                tmp = rest;
+            }
+            None => return Err(ParseError::ExpressionNotClosed(tokens[0].span)),
+        }
+    }
+
+    let body = parse(&tmp[1..], context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok((functions, body))
+}
+
+pub fn parse_flet(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (functions, body) = _parse_local_functions(tokens, context)?;
+    Ok(Operator::FLet(functions, body))
+}
+
+pub fn parse_labels(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (functions, body) = _parse_local_functions(tokens, context)?;
+    Ok(Operator::Labels(functions, body))
+}
+
+fn _parse_parameters<'a>(
+    tokens: &'a [Token],
+    context: &ParseContext,
+) -> Result<(&'a [Token], Vec<String>), ParseError> {
     let mut parameters = vec![];
     let mut temp = tokens;
 
-    while temp.len() > 0 {
-        match temp.first() {
-            Some(Token::CloseParen) => {
+    while !temp.is_empty() {
+        match temp.first().map(|token| &token.kind) {
+            Some(TokenKind::CloseParen) => {
                 temp = &temp[1..]; // Skip CloseParen
                 break;
             }
-            Some(Token::Atom(Atom::Name(name))) => {
+            Some(TokenKind::Atom(Atom::Name(name))) => {
                 temp = &temp[1..];
                 parameters.push(name.clone());
             }
-            Some(token) => {
-                return Err(ParseError::InvalidToken(token.clone()));
+            Some(_) => {
+                return Err(ParseError::InvalidToken(temp[0].clone()));
             }
             _ => {
-                return Err(ParseError::ExpressionNotClosed);
+                return Err(ParseError::ExpressionNotClosed(context.form_span));
             }
         }
     }
 
     Ok((temp, parameters))
 }
-
@@ -0,0 +1,99 @@
+use lisp::{Atom, Datum, Operator, Span, Token, TokenKind};
+
+use crate::parse::base::{parse_single_expression, ParseError};
+use crate::parse::context::ParseContext;
+
+fn _wrap_datum(name: &str, datum: Datum) -> Datum {
+    Datum::List(vec![Datum::Name(String::from(name)), datum])
+}
+
+fn _parse_datum_list<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+    in_quasiquote: bool,
+    open_span: Span,
+) -> Result<(&'a [Token], Datum), ParseError> {
+    let mut items = vec![];
+    let mut tmp = tokens;
+
+    loop {
+        match tmp.first().map(|token| &token.kind) {
+            Some(TokenKind::CloseParen) => return Ok((&tmp[1..], Datum::List(items))),
+            Some(_) => {
+                let (rest, item) = parse_datum(tmp, context, in_quasiquote)?;
+                items.push(item);
+                tmp = rest;
+            }
+            None => return Err(ParseError::ExpressionNotClosed(open_span)),
+        }
+    }
+}
+
+/// Parses a single form as data rather than code. Inside a quasiquote
+/// template `,x` and `,@x` are parsed as expressions to be evaluated.
+pub fn parse_datum<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+    in_quasiquote: bool,
+) -> Result<(&'a [Token], Datum), ParseError> {
+    let rest = tokens.get(1..).unwrap_or_default();
+    match tokens.first().map(|token| &token.kind) {
+        Some(TokenKind::OpenParen) => {
+            _parse_datum_list(rest, context, in_quasiquote, tokens[0].span)
+        }
+        Some(TokenKind::Atom(Atom::Literal(literal))) => {
+            Ok((rest, Datum::Literal(literal.clone())))
+        }
+        Some(TokenKind::Atom(Atom::Name(name))) => Ok((rest, Datum::Name(name.clone()))),
+        Some(TokenKind::Quote) => {
+            let (rest, datum) = parse_datum(rest, context, in_quasiquote)?;
+            Ok((rest, _wrap_datum("quote", datum)))
+        }
+        Some(TokenKind::Backtick) => {
+            let (rest, datum) = parse_datum(rest, context, false)?;
+            Ok((rest, _wrap_datum("quasiquote", datum)))
+        }
+        Some(TokenKind::Comma) if in_quasiquote => {
+            let (rest, expr) = parse_single_expression(rest, context)?;
+            Ok((rest, Datum::Unquote(Box::new(expr))))
+        }
+        Some(TokenKind::CommaAt) if in_quasiquote => {
+            let (rest, expr) = parse_single_expression(rest, context)?;
+            Ok((rest, Datum::UnquoteSplicing(Box::new(expr))))
+        }
+        Some(TokenKind::Comma) => {
+            let (rest, datum) = parse_datum(rest, context, false)?;
+            Ok((rest, _wrap_datum("unquote", datum)))
+        }
+        Some(TokenKind::CommaAt) => {
+            let (rest, datum) = parse_datum(rest, context, false)?;
+            Ok((rest, _wrap_datum("unquote-splicing", datum)))
+        }
+        Some(_) => Err(ParseError::InvalidToken(tokens[0].clone())),
+        None => Err(ParseError::InvalidAtom(context.form_span)),
+    }
+}
+
+fn _parse_single_datum(
+    tokens: &[Token],
+    context: &mut ParseContext,
+    in_quasiquote: bool,
+) -> Result<Datum, ParseError> {
+    match parse_datum(tokens, context, in_quasiquote)? {
+        ([], datum) => Ok(datum),
+        ([tok, ..], _) => Err(ParseError::InvalidToken(tok.clone())),
+    }
+}
+
+pub fn parse_quote(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    Ok(Operator::Quote(_parse_single_datum(tokens, context, false)?))
+}
+
+pub fn parse_quasiquote(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<Operator, ParseError> {
+    Ok(Operator::Quasiquote(_parse_single_datum(
+        tokens, context, true,
+    )?))
+}
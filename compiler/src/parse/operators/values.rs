@@ -1,15 +1,88 @@
-use lisp::{Atom, Operator, Token};
+use lisp::{Atom, Binding, Expression, ExpressionKind, Literal, Operator, Token, TokenKind};
 
-use crate::parse::base::{parse, ParseError};
+use crate::parse::base::{error_span, parse, parse_single_expression, ParseError};
 use crate::parse::context::ParseContext;
 
-pub fn parse_setq(tokens: &[Token], _context: &mut ParseContext) -> Result<Operator, ParseError> {
-    match tokens {
-        [Token::Atom(Atom::Name(name)), rest @ ..] => match parse(rest).next() {
+pub fn parse_setq(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    match tokens.first().map(|token| &token.kind) {
+        Some(TokenKind::Atom(Atom::Name(name))) => match parse(&tokens[1..], context).next() {
             Some(Ok(expr)) => Ok(Operator::SetQ(name.clone(), expr)),
-            _ => Err(ParseError::InvalidAtom),
+            Some(Err(err)) => Err(err),
+            None => Err(ParseError::InvalidAtom(context.form_span)),
         },
-        _ => Err(ParseError::InvalidAtom),
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context))),
     }
 }
 
+fn _nil(token: &Token) -> Expression {
+    Expression::new(ExpressionKind::Literal(Literal::Nil), token.span)
+}
+
+/// Parses a single `name`, `(name)` or `(name value)` binding, where a
+/// missing value means `nil`.
+fn _parse_binding<'a>(
+    tokens: &'a [Token],
+    context: &mut ParseContext,
+) -> Result<(&'a [Token], Binding), ParseError> {
+    match (tokens.first().map(|token| &token.kind), tokens.get(1)) {
+        (Some(TokenKind::Atom(Atom::Name(name))), _) => {
+            Ok((&tokens[1..], (name.clone(), _nil(&tokens[0]))))
+        }
+        (Some(TokenKind::OpenParen), Some(name_token)) => {
+            let name = match &name_token.kind {
+                TokenKind::Atom(Atom::Name(name)) => name.clone(),
+                _ => return Err(ParseError::InvalidToken(name_token.clone())),
+            };
+            if let Some(Token { kind: TokenKind::CloseParen, .. }) = tokens.get(2) {
+                return Ok((&tokens[3..], (name, _nil(name_token))));
+            }
+
+            let (rest, value) = parse_single_expression(&tokens[2..], context)?;
+            match rest.first() {
+                Some(Token { kind: TokenKind::CloseParen, .. }) => Ok((&rest[1..], (name, value))),
+                Some(token) => Err(ParseError::InvalidToken(token.clone())),
+                None => Err(ParseError::ExpressionNotClosed(tokens[0].span)),
+            }
+        }
+        _ => Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    }
+}
+
+/// Parses the `((name value) ...)` list that opens a `let` or `let*`,
+/// followed by the body forms.
+fn _parse_let_parts(
+    tokens: &[Token],
+    context: &mut ParseContext,
+) -> Result<(Vec<Binding>, Vec<Expression>), ParseError> {
+    match tokens.first().map(|token| &token.kind) {
+        Some(TokenKind::OpenParen) => {}
+        _ => return Err(ParseError::InvalidAtom(error_span(tokens, context))),
+    }
+
+    let mut bindings = vec![];
+    let mut tmp = &tokens[1..];
+    loop {
+        match tmp.first().map(|token| &token.kind) {
+            Some(TokenKind::CloseParen) => break,
+            Some(_) => {
+                let (rest, binding) = _parse_binding(tmp, context)?;
+                bindings.push(binding);
+                tmp = rest;
+            }
+            None => return Err(ParseError::ExpressionNotClosed(tokens[0].span)),
+        }
+    }
+
+    let body = parse(&tmp[1..], context).collect::<Result<Vec<Expression>, ParseError>>()?;
+    Ok((bindings, body))
+}
+
+pub fn parse_let(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (bindings, body) = _parse_let_parts(tokens, context)?;
+    Ok(Operator::Let(bindings, body))
+}
+
+pub fn parse_let_star(tokens: &[Token], context: &mut ParseContext) -> Result<Operator, ParseError> {
+    let (bindings, body) = _parse_let_parts(tokens, context)?;
+    Ok(Operator::LetStar(bindings, body))
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use common::{compile, number, run};
+use compiler::codegen::CodegenError;
+use vm::bytecode::Value;
+use vm::vm::execute::{self, ExecuteError};
+
+#[test]
+fn arithmetic_folds_from_the_left() {
+    assert_eq!(run("(+ 1 2 3)"), number(6));
+    assert_eq!(run("(- 10 3 2)"), number(5));
+    assert_eq!(run("(* 2 3 4)"), number(24));
+    assert_eq!(run("(/ 20 2 5)"), number(2));
+    assert_eq!(run("(+ (* 2 3) (- 10 4))"), number(12));
+}
+
+#[test]
+fn unary_minus_negates() {
+    assert_eq!(run("(- 5)"), number(-5));
+    assert_eq!(run("(- (- 5))"), number(5));
+    assert_eq!(run("(+ 1 (- 3))"), number(-2));
+}
+
+#[test]
+fn if_picks_a_branch() {
+    assert_eq!(run("(if (= 1 1) 10 20)"), number(10));
+    assert_eq!(run("(if (= 1 2) 10 20)"), number(20));
+    assert_eq!(run("(if (= 1 2) 10)"), Value::Boolean(false));
+    assert_eq!(run("(if (< 1 2) (if (> 1 2) 1 2) 3)"), number(2));
+}
+
+#[test]
+fn setq_defines_and_updates_locals() {
+    assert_eq!(run("(setq x 5)"), number(5));
+    assert_eq!(run("(setq x 5) (setq x (+ x 1)) x"), number(6));
+    assert_eq!(run("(setq x 2) (setq y 3) (* x y)"), number(6));
+}
+
+#[test]
+fn calls_run_function_chunks() {
+    assert_eq!(run("(defun square (x) (* x x)) (square 7)"), number(49));
+    assert_eq!(run("(defun sub (a b) (- a b)) (sub 10 4)"), number(6));
+    assert_eq!(
+        run("(defun fact (n) (if (= n 0) 1 (* n (fact (- n 1))))) (fact 10)"),
+        number(3628800)
+    );
+    assert_eq!(
+        run("(defun twice (x) (double (double x))) (defun double (x) (* 2 x)) (twice 3)"),
+        number(12)
+    );
+}
+
+#[test]
+fn stack_overflows_name_the_functions_on_the_stack() {
+    let mut vm = compile(
+        "(defun down (n) (+ 1 (down n)))
+         (defun start () (down 0))
+         (start)",
+    )
+    .unwrap();
+    vm.call_stack.set_max_depth(10);
+    match execute::run(&mut vm) {
+        Err(ExecuteError::StackOverflow(depth, names)) => {
+            assert_eq!(depth, 11);
+            assert_eq!(names.len(), 11);
+            assert!(names[..9].iter().all(|name| name == "down"));
+            assert_eq!(names[9], "start");
+            assert_eq!(names[10], "toplevel");
+        }
+        other => panic!("expected a stack overflow, got {:?}", other),
+    }
+}
+
+#[test]
+fn function_bodies_run_every_form() {
+    assert_eq!(run("(defun two () 1 2) (two)"), number(2));
+    assert_eq!(run("(defun bump (x) (setq x (+ x 1)) (* x 10)) (bump 2)"), number(30));
+}
+
+#[test]
+fn divisions_that_are_not_whole_are_rejected() {
+    for source in ["(/ 7 2)", "(/ 1 0)", "(setq x 4) (/ x 2)", "(/ 20 2 3)"] {
+        assert!(
+            matches!(compile(source), Err(CodegenError::UnsupportedArithmetic(name)) if name == "/"),
+            "{} should not compile",
+            source
+        );
+    }
+}
+
+#[test]
+fn arithmetic_on_literals_that_overflows_is_rejected() {
+    for source in [
+        "(+ 9223372036854775807 1)",
+        "(- -9223372036854775808 1)",
+        "(* 4611686018427387904 2)",
+        "(- -9223372036854775808)",
+        "(+ 1 (* 9223372036854775807 2))",
+    ] {
+        assert!(
+            matches!(compile(source), Err(CodegenError::UnsupportedArithmetic(_))),
+            "{} should not compile",
+            source
+        );
+    }
+    assert_eq!(run("(+ 9223372036854775806 1)"), number(i64::MAX));
+    assert_eq!(run("(- -9223372036854775807)"), number(i64::MAX));
+}
+
+#[test]
+fn overflows_at_runtime_fail() {
+    let mut vm = compile("(setq x 9223372036854775807) (+ x 1)").unwrap();
+    assert!(matches!(execute::run(&mut vm), Err(ExecuteError::IntegerOverflow)));
+}
@@ -0,0 +1,33 @@
+//! Fixtures shared by the integration tests, which each use only some of
+//! them.
+#![allow(dead_code)]
+
+use compiler::codegen::{self, CodegenError};
+use compiler::lex;
+use compiler::parse::{self, ParseContext, ParseError};
+use lisp::Expression;
+use vm::bytecode::Value;
+use vm::vm::{execute, Vm};
+
+/// Lexes and parses every form in `source`.
+pub fn parse(source: &str) -> Result<Vec<Expression>, ParseError> {
+    let tokens = lex::lex(source).unwrap();
+    parse::parse(&tokens, &mut ParseContext::new()).collect()
+}
+
+/// Lexes, parses and generates code for `source`, ready to run.
+pub fn compile(source: &str) -> Result<Vm, CodegenError> {
+    let expressions = parse(source).unwrap();
+    Ok(Vm::new(codegen::generate(&expressions)?))
+}
+
+/// Compiles and runs `source`, returning the value it leaves on the stack.
+pub fn run(source: &str) -> Value {
+    let mut vm = compile(source).unwrap();
+    execute::run(&mut vm).unwrap();
+    vm.peek().unwrap()
+}
+
+pub fn number(value: i64) -> Value {
+    Value::Literal(value)
+}
@@ -0,0 +1,180 @@
+use compiler::lex::{lex, LexError};
+use lisp::{Atom, Literal, TokenKind};
+
+/// Lexes `source`, which must be a single string literal, to its contents.
+fn string(source: &str) -> String {
+    let tokens = lex(source).unwrap();
+    match &tokens[..] {
+        [token] => match &token.kind {
+            TokenKind::Atom(Atom::Literal(Literal::String(string))) => string.clone(),
+            kind => panic!("expected a string, got {:?}", kind),
+        },
+        tokens => panic!("expected one token, got {:?}", tokens),
+    }
+}
+
+fn error(source: &str) -> LexError {
+    lex(source).unwrap_err()
+}
+
+/// Lexes `source`, which must be a single atom.
+fn atom(source: &str) -> Atom {
+    let tokens = lex(source).unwrap();
+    match &tokens[..] {
+        [token] => match &token.kind {
+            TokenKind::Atom(atom) => atom.clone(),
+            kind => panic!("expected an atom, got {:?}", kind),
+        },
+        tokens => panic!("expected one token, got {:?}", tokens),
+    }
+}
+
+#[test]
+fn escapes_stand_for_their_characters() {
+    assert_eq!(string(r#""say \"hi\"""#), "say \"hi\"");
+    assert_eq!(string(r#""back\\slash""#), "back\\slash");
+    assert_eq!(string(r#""a\nb""#), "a\nb");
+    assert_eq!(string(r#""a\tb""#), "a\tb");
+    assert_eq!(string(r#""a\rb""#), "a\rb");
+    assert_eq!(string(r#""\\\"""#), "\\\"");
+}
+
+#[test]
+fn unicode_escapes_take_one_to_six_hex_digits() {
+    assert_eq!(string(r#""\u{41}""#), "A");
+    assert_eq!(string(r#""\u{e9}t\u{E9}""#), "été");
+    assert_eq!(string(r#""\u{1F600}""#), "\u{1F600}");
+    assert_eq!(string(r#""\u{10FFFF}""#), "\u{10FFFF}");
+}
+
+#[test]
+fn malformed_unicode_escapes_fail() {
+    for source in [
+        r#""\u41""#,
+        r#""\u{}""#,
+        r#""\u{1234567}""#,
+        r#""\u{110000}""#,
+        r#""\u{D800}""#,
+        r#""\u{4g}""#,
+    ] {
+        assert!(
+            matches!(error(source), LexError::InvalidUnicodeEscape(_)),
+            "{} should be an invalid unicode escape",
+            source
+        );
+    }
+}
+
+#[test]
+fn unknown_escapes_fail_at_the_escape() {
+    match error(r#"(print "ab\qc")"#) {
+        LexError::InvalidEscape('q', span) => {
+            assert_eq!((span.column, span.length), (11, 2));
+        }
+        other => panic!("expected an invalid escape, got {:?}", other),
+    }
+    assert!(matches!(error(r#""\a""#), LexError::InvalidEscape('a', _)));
+    assert!(matches!(error(r#""\'""#), LexError::InvalidEscape('\'', _)));
+    assert!(matches!(error("\"\\\n\""), LexError::InvalidEscape('\n', _)));
+    assert_eq!(error(r#""\x""#).to_string(), "unknown escape sequence '\\x'");
+    assert_eq!(error("\"\\\n\"").to_string(), "unknown escape sequence '\\\\n'");
+}
+
+#[test]
+fn a_trailing_backslash_leaves_the_string_open() {
+    for source in [r#""abc\"#, r#""abc\""#, r#"(f "abc\"#, r#""\u"#, r#""\u{41"#] {
+        match error(source) {
+            LexError::StringNeverClosed(span) => {
+                assert_eq!(span.offset, source.find('"').unwrap(), "{}", source)
+            }
+            other => panic!("{} should never close, got {:?}", source, other),
+        }
+    }
+}
+
+#[test]
+fn printed_strings_read_back() {
+    for contents in ["plain", "say \"hi\"", "back\\slash", "tab\tand\nnewline\r", "été"] {
+        let printed = Literal::String(contents.to_string()).to_string();
+        assert_eq!(string(&printed), contents, "{} should read back", printed);
+    }
+}
+
+#[test]
+fn printed_floats_read_back() {
+    for float in [1.0, -0.25, 1e20, 1.5e-7, -2.5e300, f64::MAX, f64::MIN_POSITIVE, 5e-324] {
+        let printed = Literal::Float(float).to_string();
+        let read = atom(&printed);
+        assert_eq!(read, Atom::Literal(Literal::Float(float)), "{} should read back", printed);
+    }
+    assert_eq!(Literal::Float(1e20).to_string(), "1.0e20");
+    assert_eq!(Literal::Float(2.5e-7).to_string(), "2.5e-7");
+}
+
+#[test]
+fn raw_strings_take_their_contents_verbatim() {
+    assert_eq!(string(r#"#r"a\nb""#), "a\\nb");
+    assert_eq!(string(r#"#r"\""#), "\\");
+    assert_eq!(string(r#"#r"""#), "");
+    assert_eq!(string("#r\"two\nlines\""), "two\nlines");
+    assert_eq!(string(r#"#r"\u{41}""#), "\\u{41}");
+}
+
+#[test]
+fn raw_strings_with_hashes_can_hold_quotes() {
+    assert_eq!(string(r##"#r#"say "hi""#"##), "say \"hi\"");
+    assert_eq!(string(r###"#r##"a "# b"##"###), "a \"# b");
+    assert_eq!(string(r##"#r#""""#"##), "\"\"");
+}
+
+#[test]
+fn raw_strings_end_where_their_closing_quote_does() {
+    let tokens = lex(r#"(f #r"a" "b")"#).unwrap();
+    let kinds = tokens.iter().map(|token| token.kind.clone()).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::OpenParen,
+            TokenKind::Atom(Atom::Name(String::from("f"))),
+            TokenKind::Atom(Atom::Literal(Literal::String(String::from("a")))),
+            TokenKind::Atom(Atom::Literal(Literal::String(String::from("b")))),
+            TokenKind::CloseParen,
+        ]
+    );
+}
+
+#[test]
+fn malformed_raw_strings_fail() {
+    assert!(matches!(error(r#"#r"abc"#), LexError::StringNeverClosed(_)));
+    assert!(matches!(error(r##"#r#"abc""##), LexError::StringNeverClosed(_)));
+    assert!(matches!(error("#r"), LexError::StringNeverClosed(_)));
+    assert!(matches!(error("#rx"), LexError::InvalidLiteral('x', _)));
+    assert!(matches!(error(r#"#q"a""#), LexError::InvalidLiteral('#', _)));
+}
+
+#[test]
+fn exponents_make_floats() {
+    assert_eq!(atom("1e3"), Atom::Literal(Literal::Float(1000.0)));
+    assert_eq!(atom("1E3"), Atom::Literal(Literal::Float(1000.0)));
+    assert_eq!(atom("2.5e-3"), Atom::Literal(Literal::Float(0.0025)));
+    assert_eq!(atom("-1e+2"), Atom::Literal(Literal::Float(-100.0)));
+    assert_eq!(atom("1e20"), Atom::Literal(Literal::Float(1e20)));
+}
+
+#[test]
+fn names_can_still_start_with_digits() {
+    assert_eq!(atom("1+"), Atom::Name(String::from("1+")));
+    assert_eq!(atom("1e"), Atom::Name(String::from("1e")));
+    assert_eq!(atom("1e3x"), Atom::Name(String::from("1e3x")));
+}
+
+#[test]
+fn floats_out_of_range_fail() {
+    for source in ["1e999", "-1e999", "1/2e3"] {
+        assert!(
+            matches!(error(source), LexError::NumberParsingFailed(_)),
+            "{} should fail to parse",
+            source
+        );
+    }
+}
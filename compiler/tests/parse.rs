@@ -0,0 +1,83 @@
+mod common;
+
+use common::parse;
+use compiler::parse::ParseError;
+use lisp::{CaseClause, ExpressionKind, Operator};
+
+/// Parses `source`, which must be a single special form.
+fn operator(source: &str) -> Operator {
+    let expressions = parse(source).unwrap();
+    match &expressions[..] {
+        [expr] => match &*expr.kind {
+            ExpressionKind::Operator(operator) => (**operator).clone(),
+            kind => panic!("expected a special form, got {:?}", kind),
+        },
+        expressions => panic!("expected one form, got {:?}", expressions),
+    }
+}
+
+#[test]
+fn blocks_can_be_named_nil() {
+    match operator("(block nil 1 2)") {
+        Operator::Block(name, body) => {
+            assert_eq!(name, "nil");
+            assert_eq!(body.len(), 2);
+        }
+        other => panic!("expected a block, got {:?}", other),
+    }
+    match operator("(return-from nil 1)") {
+        Operator::ReturnFrom(name, Some(_)) => assert_eq!(name, "nil"),
+        other => panic!("expected a return-from, got {:?}", other),
+    }
+    assert!(matches!(operator("(return-from nil)"), Operator::ReturnFrom(_, None)));
+}
+
+#[test]
+fn blocks_need_a_name() {
+    assert!(parse("(block 1 2)").is_err());
+    assert!(parse("(block)").is_err());
+    assert!(parse("(return-from \"x\" 1)").is_err());
+}
+
+/// The number of keys in each clause of the `case` form in `source`, `None`
+/// for `otherwise`.
+fn case_keys(source: &str) -> Vec<Option<usize>> {
+    match operator(source) {
+        Operator::Case(_, clauses) | Operator::ECase(_, clauses) => clauses
+            .iter()
+            .map(|(keys, _): &CaseClause| keys.as_ref().map(Vec::len))
+            .collect(),
+        other => panic!("expected a case, got {:?}", other),
+    }
+}
+
+#[test]
+fn case_keys_can_be_a_list_or_a_single_key() {
+    assert_eq!(
+        case_keys("(case x ((1 2) 'a) (3 'b) (otherwise 'c))"),
+        [Some(2), Some(1), None]
+    );
+    assert_eq!(case_keys("(ecase x (a 1) (t 2))"), [Some(1), None]);
+}
+
+#[test]
+fn nil_case_keys_are_an_empty_list() {
+    assert_eq!(case_keys("(case x (nil 1) ((nil) 2))"), [Some(0), Some(1)]);
+}
+
+#[test]
+fn malformed_clauses_are_rejected() {
+    for source in [
+        "(cond 1)",
+        "(cond ())",
+        "(case x 1)",
+        "(case x ())",
+        "(ecase x (otherwise 1) (2 2))",
+    ] {
+        assert!(
+            matches!(parse(source), Err(ParseError::InvalidClause(..))),
+            "{} should have an invalid clause",
+            source
+        );
+    }
+}
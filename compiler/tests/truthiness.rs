@@ -0,0 +1,18 @@
+mod common;
+
+use common::run;
+use vm::bytecode::Value;
+
+#[test]
+fn jumps_treat_everything_but_nil_as_true() {
+    assert_eq!(run("(if 1 10 20)"), Value::Literal(10));
+    assert_eq!(run("(if 0 10 20)"), Value::Literal(10));
+    assert_eq!(run("(if t 10 20)"), Value::Literal(10));
+    assert_eq!(run("(if nil 10 20)"), Value::Literal(20));
+}
+
+#[test]
+fn comparisons_drive_jumps() {
+    assert_eq!(run("(if (< 1 2) 10 20)"), Value::Literal(10));
+    assert_eq!(run("(if (> 1 2) 10 20)"), Value::Literal(20));
+}
@@ -1,5 +1,10 @@
-use crate::{frame::EvalContext, value::Value};
-use lisp::Expression;
+use std::fmt;
+
+use crate::{
+    frame::{EvalContext, Scope},
+    value::Value,
+};
+use lisp::{Expression, ExpressionKind, Literal, Span};
 
 use crate::{
     function::invoke_function,
@@ -12,28 +17,304 @@ pub enum EvalError {
     UndefinedBehaviour,
     NameNotFound(String),
     NotCallable(String),
+    InvalidMacroExpansion,
+    /// A condition raised from Lisp with `error`, carrying its value.
+    User(Value),
+    /// The evaluation depth limit was hit. Holds the depth and the names of
+    /// the functions on the stack, innermost first.
+    StackOverflow(usize, Vec<String>),
+    DivisionByZero,
+    /// An exact result would be too large to compute, like `expt` with a
+    /// huge exponent.
+    NumberTooLarge,
+    /// An index into a sequence was past its end. Holds the index and the
+    /// length.
+    IndexOutOfBounds(i64, usize),
+    /// A range of a sequence started after its end. Holds the start and the
+    /// end.
+    InvalidRange(usize, usize),
+    /// An argument was not of the expected type, named by the first field.
+    TypeMismatch(&'static str, Value),
+    /// Not a failure but a non-local exit unwinding to the form that
+    /// handles it.
+    Signal(Signal),
+    /// A `return-from` named a block that isn't visible.
+    UnknownBlock(String),
+    /// A `throw` had no `catch` with its tag to go to.
+    UnknownCatchTag(Value),
+    /// No clause of an `ecase` matched the value.
+    UnmatchedCase(Value),
+    /// Wraps an error with the span of the innermost expression it came from.
+    Located(Box<EvalError>, Span),
+    /// Wraps a located error with the function calls it unwound through,
+    /// innermost first.
+    Traced(Box<EvalError>, Vec<TraceFrame>),
+}
+
+#[derive(Debug)]
+pub enum Signal {
+    /// `return-from` the block with the given id and name.
+    ReturnFrom(usize, String, Value),
+    /// `throw` to the `catch` with the given tag.
+    Throw(Value, Value),
+}
+
+/// A function call an error unwound through: the function's name and the
+/// span of the call.
+#[derive(Clone, Debug)]
+pub struct TraceFrame {
+    pub name: String,
+    pub span: Span,
 }
 
 pub type EvalResult = Result<Value, EvalError>;
 
-pub fn eval(expr: &Expression, context: &mut EvalContext) -> EvalResult {
-    match expr {
-        Expression::Name(name) => {
-            if let Some(value) = context.lookup_local(name) {
-                return Ok(value.clone());
-            } else {
-                return Err(EvalError::NameNotFound(String::from(name)));
+impl EvalError {
+    /// Attaches `span` unless the error already points at a narrower one.
+    pub fn at(self, span: Span) -> Self {
+        match self {
+            EvalError::Located(..) | EvalError::Traced(..) | EvalError::Signal(_) => self,
+            error => EvalError::Located(Box::new(error), span),
+        }
+    }
+
+    /// Records that the error unwound through a call to `name` at `span`.
+    pub fn traced(self, name: &str, span: Span) -> Self {
+        if let EvalError::Signal(_) = self {
+            return self;
+        }
+
+        let frame = TraceFrame {
+            name: name.to_string(),
+            span,
+        };
+        match self {
+            EvalError::Traced(error, mut frames) => {
+                frames.push(frame);
+                EvalError::Traced(error, frames)
             }
+            error => EvalError::Traced(Box::new(error), vec![frame]),
+        }
+    }
+
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            EvalError::Located(_, span) => Some(*span),
+            EvalError::Traced(error, _) => error.span(),
+            _ => None,
+        }
+    }
+
+    pub fn is_signal(&self) -> bool {
+        matches!(self, EvalError::Signal(_))
+    }
+
+    /// Turns a `return-from` aimed at `block` into the value it returns,
+    /// passing anything else through.
+    pub fn returned_from(self, block: Option<usize>) -> EvalResult {
+        match self {
+            EvalError::Signal(Signal::ReturnFrom(id, _, value)) if Some(id) == block => Ok(value),
+            error => Err(error),
+        }
+    }
+
+    /// Returns the error without the span and backtrace wrapped around it.
+    pub fn root(&self) -> &EvalError {
+        match self {
+            EvalError::Located(error, _) | EvalError::Traced(error, _) => error.root(),
+            error => error,
+        }
+    }
+
+    /// The condition type `handler-case` matches this error against.
+    /// Every error also matches `error`, `condition` and `t`.
+    pub fn condition_type(&self) -> &'static str {
+        match self.root() {
+            EvalError::User(_) => "simple-error",
+            EvalError::BadArguments | EvalError::InvalidMacroExpansion => "program-error",
+            EvalError::TypeMismatch(..)
+            | EvalError::NotCallable(_)
+            | EvalError::UnmatchedCase(_)
+            | EvalError::IndexOutOfBounds(..)
+            | EvalError::InvalidRange(..) => "type-error",
+            EvalError::NameNotFound(_) => "unbound-variable",
+            EvalError::StackOverflow(..) => "stack-overflow",
+            EvalError::DivisionByZero => "division-by-zero",
+            EvalError::NumberTooLarge => "arithmetic-error",
+            _ => "error",
+        }
+    }
+
+    /// The value a `handler-case` clause binds: what was passed to `error`,
+    /// or the message of a built-in error.
+    pub fn condition_value(&self) -> Value {
+        match self.root() {
+            EvalError::User(value) => value.clone(),
+            error => Value::Literal(Literal::String(error.to_string())),
+        }
+    }
+
+    /// The calls the error unwound through, innermost first.
+    pub fn backtrace(&self) -> &[TraceFrame] {
+        match self {
+            EvalError::Traced(_, frames) => frames,
+            _ => &[],
         }
-        Expression::Literal(literal) => {
-            return Ok(Value::Literal(literal.clone()));
+    }
+}
+
+impl fmt::Display for EvalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EvalError::BadArguments => write!(f, "bad arguments"),
+            EvalError::UndefinedBehaviour => write!(f, "undefined behaviour"),
+            EvalError::NameNotFound(name) => write!(f, "name not found: {}", name),
+            EvalError::NotCallable(name) => write!(f, "not callable: {}", name),
+            EvalError::InvalidMacroExpansion => write!(f, "invalid macro expansion"),
+            EvalError::User(Value::Literal(Literal::String(message))) => write!(f, "{}", message),
+            EvalError::User(value) => write!(f, "{}", value),
+            EvalError::StackOverflow(depth, names) => {
+                write!(f, "stack overflow at depth {}", depth)?;
+                _write_call_names(f, names)
+            }
+            EvalError::DivisionByZero => write!(f, "division by zero"),
+            EvalError::NumberTooLarge => write!(f, "number too large"),
+            EvalError::IndexOutOfBounds(index, length) => {
+                write!(f, "index {} is out of bounds for length {}", index, length)
+            }
+            EvalError::InvalidRange(start, end) => write!(f, "start {} is after end {}", start, end),
+            EvalError::TypeMismatch(expected, value) => {
+                write!(f, "expected {}, got {}", expected, value)
+            }
+            EvalError::Signal(Signal::ReturnFrom(_, name, _)) => {
+                write!(f, "return-from {} after its block has exited", name)
+            }
+            EvalError::Signal(Signal::Throw(tag, _)) => write!(f, "throw to {} after its catch has exited", tag),
+            EvalError::UnknownBlock(name) => write!(f, "no block named {}", name),
+            EvalError::UnknownCatchTag(tag) => write!(f, "no catch for tag {}", tag),
+            EvalError::UnmatchedCase(value) => write!(f, "no ecase clause matches {}", value),
+            EvalError::Located(error, _) | EvalError::Traced(error, _) => error.fmt(f),
         }
-        Expression::Call(name, expressions) => match context.lookup_local(name) {
+    }
+}
+
+/// What is left to do after evaluating one step of an expression. Tail
+/// positions come back as `Eval` or `Enter` so `eval` can continue with
+/// them in a loop instead of recursing.
+pub enum TailStep {
+    Value(Value),
+    /// Continue with an expression in the current scope.
+    Eval(Expression),
+    /// Continue with an expression in a new scope, such as the body of a
+    /// `let`.
+    Enter(Scope, Expression),
+    /// Continue with the body of the named function in the scope binding
+    /// its arguments.
+    Call(String, Scope, Expression),
+}
+
+fn _eval_step(expr: &Expression, context: &mut EvalContext) -> Result<TailStep, EvalError> {
+    match &*expr.kind {
+        ExpressionKind::Name(name) => match context.lookup_local(name) {
+            Some(value) => Ok(TailStep::Value(value)),
+            None => Err(EvalError::NameNotFound(String::from(name))),
+        },
+        ExpressionKind::Literal(literal) => Ok(TailStep::Value(Value::Literal(literal.clone()))),
+        ExpressionKind::Call(name, expressions) => match context.lookup_local(name) {
             Some(Value::Symbol(function)) => invoke_function(&*function, expressions, context),
             Some(_) => Err(EvalError::NotCallable(String::from(name))),
             _ => Err(EvalError::NameNotFound(String::from(name))),
         },
-        Expression::Operator(operator) => eval_operator(*operator.clone(), context),
+        ExpressionKind::Operator(operator) => eval_operator(operator, expr.span, context),
         _ => Err(EvalError::UndefinedBehaviour),
     }
 }
+
+/// Writes `names` with runs of the same name collapsed, so deep recursion
+/// reads as `f (1000 times)`.
+fn _write_call_names(f: &mut fmt::Formatter<'_>, names: &[String]) -> fmt::Result {
+    let mut index = 0;
+    while index < names.len() {
+        let name = &names[index];
+        let count = names[index..].iter().take_while(|other| *other == name).count();
+        let separator = if index == 0 { ": " } else { " <- " };
+        match count {
+            1 => write!(f, "{}{}", separator, name)?,
+            _ => write!(f, "{}{} ({} times)", separator, name, count)?,
+        }
+        index += count;
+    }
+    Ok(())
+}
+
+/// Evaluates `expr`. Every call nests native stack frames, so each one
+/// counts toward the context's depth limit.
+pub fn eval(expr: &Expression, context: &mut EvalContext) -> EvalResult {
+    if let Err(error) = context.enter_eval() {
+        return Err(error.at(expr.span));
+    }
+    let result = _eval_tail_loop(expr, context);
+    context.exit_eval();
+    result
+}
+
+/// Evaluates `expr` step by step, continuing with tail positions in a loop
+/// instead of recursing.
+fn _eval_tail_loop(expr: &Expression, context: &mut EvalContext) -> EvalResult {
+    let mut tail: Option<Expression> = None;
+    // The scope to restore once done, set when the first function body is
+    // entered. Later tail calls replace the function scope in place, which
+    // is what keeps tail recursion from growing the stack.
+    let mut outer_scope = None;
+    // The function whose body is being evaluated and the span of the call
+    // that entered it, for backtraces.
+    let mut call: Option<(String, Span)> = None;
+    // The id of the function's implicit block.
+    let mut block = None;
+
+    let result = loop {
+        let current = tail.as_ref().unwrap_or(expr);
+        let span = current.span;
+        match _eval_step(current, context) {
+            Ok(TailStep::Value(value)) => break Ok(value),
+            Ok(TailStep::Eval(next)) => tail = Some(next),
+            Ok(TailStep::Enter(scope, body)) => {
+                let previous = context.enter_scope(scope);
+                outer_scope.get_or_insert(previous);
+                tail = Some(body);
+            }
+            Ok(TailStep::Call(name, scope, body)) => {
+                // A tail call takes over the caller's place on the call stack.
+                if call.is_some() {
+                    context.replace_call(&name);
+                } else {
+                    context.enter_call(&name);
+                }
+                block = scope.borrow().blocks.get(&name).copied();
+                call = Some((name, span));
+
+                let previous = context.enter_scope(scope);
+                outer_scope.get_or_insert(previous);
+                tail = Some(body);
+            }
+            Err(error) => {
+                let error = match error.returned_from(block) {
+                    Ok(value) => break Ok(value),
+                    Err(error) => error.at(span),
+                };
+                break Err(match &call {
+                    Some((name, call_span)) => error.traced(name, *call_span),
+                    None => error,
+                });
+            }
+        }
+    };
+
+    if let Some(previous) = outer_scope {
+        context.exit_scope(previous);
+    }
+    if call.is_some() {
+        context.exit_call();
+    }
+    result
+}
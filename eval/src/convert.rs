@@ -0,0 +1,148 @@
+use lisp::Literal;
+
+use crate::{base::EvalError, number::Number, value::Value};
+
+/// Converts a Lisp value into a Rust type, failing with a type mismatch
+/// when the value has the wrong shape.
+pub trait FromValue: Sized {
+    fn from_value(value: Value) -> Result<Self, EvalError>;
+}
+
+pub trait IntoValue {
+    fn into_value(self) -> Value;
+}
+
+/// The result of a native function: either a plain value or a `Result`
+/// for functions that can fail.
+pub trait IntoEvalResult {
+    fn into_eval_result(self) -> Result<Value, EvalError>;
+}
+
+impl FromValue for Value {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        Ok(value)
+    }
+}
+
+impl IntoValue for Value {
+    fn into_value(self) -> Value {
+        self
+    }
+}
+
+/// Accepts any number, converting integers to floats.
+impl FromValue for f64 {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        Number::from_value(value).map(|number| number.to_f64())
+    }
+}
+
+impl IntoValue for f64 {
+    fn into_value(self) -> Value {
+        Value::Literal(Literal::Float(self))
+    }
+}
+
+impl FromValue for i64 {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        match value {
+            Value::Literal(Literal::Integer(integer)) => Ok(integer),
+            value => Err(EvalError::TypeMismatch("integer", value)),
+        }
+    }
+}
+
+impl IntoValue for i64 {
+    fn into_value(self) -> Value {
+        Value::Literal(Literal::Integer(self))
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        match value {
+            Value::Literal(Literal::String(string)) => Ok(string),
+            value => Err(EvalError::TypeMismatch("string", value)),
+        }
+    }
+}
+
+impl IntoValue for String {
+    fn into_value(self) -> Value {
+        Value::Literal(Literal::String(self))
+    }
+}
+
+impl IntoValue for &str {
+    fn into_value(self) -> Value {
+        self.to_string().into_value()
+    }
+}
+
+/// Anything but `nil` counts as true.
+impl FromValue for bool {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        Ok(value.is_true())
+    }
+}
+
+impl IntoValue for bool {
+    fn into_value(self) -> Value {
+        match self {
+            true => Value::Literal(Literal::True),
+            false => Value::Literal(Literal::Nil),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        match value.to_vec() {
+            Some(values) => values.into_iter().map(T::from_value).collect(),
+            None => Err(EvalError::TypeMismatch("list", value)),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn into_value(self) -> Value {
+        Value::list(self.into_iter().map(T::into_value).collect())
+    }
+}
+
+/// `nil` maps to `None`, anything else has to convert to `T`.
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        match value {
+            Value::Literal(Literal::Nil) => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn into_value(self) -> Value {
+        match self {
+            Some(value) => value.into_value(),
+            None => Value::Literal(Literal::Nil),
+        }
+    }
+}
+
+impl IntoValue for () {
+    fn into_value(self) -> Value {
+        Value::Literal(Literal::Nil)
+    }
+}
+
+impl<T: IntoValue> IntoEvalResult for T {
+    fn into_eval_result(self) -> Result<Value, EvalError> {
+        Ok(self.into_value())
+    }
+}
+
+impl<T: IntoValue> IntoEvalResult for Result<T, EvalError> {
+    fn into_eval_result(self) -> Result<Value, EvalError> {
+        self.map(T::into_value)
+    }
+}
@@ -0,0 +1,80 @@
+use compiler::parse::{parse_single_expression, ParseContext};
+use lisp::{Atom, Expression, Span, Token, TokenKind};
+
+use crate::{
+    base::{EvalError, EvalResult},
+    frame::EvalContext,
+    function::Function,
+    value::Value,
+};
+
+fn _push_tokens(value: &Value, tokens: &mut Vec<Token>, span: Span) -> Result<(), EvalError> {
+    let kind = match value {
+        Value::Literal(literal) => TokenKind::Atom(Atom::Literal(literal.clone())),
+        Value::Name(name) => TokenKind::Atom(Atom::Name(name.clone())),
+        Value::Cons(_) => {
+            let items = value.to_vec().ok_or(EvalError::InvalidMacroExpansion)?;
+            tokens.push(Token::new(TokenKind::OpenParen, span));
+            for item in &items {
+                _push_tokens(item, tokens, span)?;
+            }
+            TokenKind::CloseParen
+        }
+        Value::Symbol(_) => return Err(EvalError::InvalidMacroExpansion),
+    };
+    tokens.push(Token::new(kind, span));
+    Ok(())
+}
+
+/// Turns a form built at runtime back into an expression, parsing it with
+/// the macros defined so far. Every part of the expression is attributed to
+/// `span`, the form that produced it.
+pub fn to_expression(
+    form: &Value,
+    span: Span,
+    context: &EvalContext,
+) -> Result<Expression, EvalError> {
+    let mut tokens = vec![];
+    _push_tokens(form, &mut tokens, span)?;
+
+    let mut parse_context = ParseContext::new();
+    parse_context.macros.extend(context.macro_names().cloned());
+    match parse_single_expression(&tokens, &mut parse_context) {
+        Ok(([], expr)) => Ok(expr),
+        _ => Err(EvalError::InvalidMacroExpansion),
+    }
+}
+
+pub fn call_macro(
+    function: &dyn Function,
+    arguments: Vec<Value>,
+    context: &mut EvalContext,
+) -> EvalResult {
+    if !function.get_arguments_size().contains(arguments.len()) {
+        return Err(EvalError::BadArguments);
+    }
+
+    function.eval(arguments, context)
+}
+
+/// Expands `form` once if it is a call to a macro, returning `None` otherwise.
+pub fn expand_macro_1(form: &Value, context: &mut EvalContext) -> Result<Option<Value>, EvalError> {
+    if let Value::Cons(cons) = form {
+        if let Value::Name(name) = &cons.car {
+            if let Some(Value::Symbol(function)) = context.lookup_macro(name) {
+                let arguments = cons.cdr.to_vec().ok_or(EvalError::BadArguments)?;
+                return call_macro(&*function, arguments, context).map(Some);
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+pub fn expand_macro(form: &Value, context: &mut EvalContext) -> EvalResult {
+    let mut form = form.clone();
+    while let Some(expanded) = expand_macro_1(&form, context)? {
+        form = expanded;
+    }
+    Ok(form)
+}
@@ -1,21 +1,62 @@
-use std::collections::{HashMap, LinkedList};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::fmt;
+use std::rc::Rc;
 
 use crate::{
-    function::builtin::create_builtin_functions_map,
+    base::EvalError,
+    function::{
+        builtin::create_builtin_functions_map,
+        native::IntoNativeFunction,
+        Function,
+    },
     value::Value,
 };
 
+pub type Scope = Rc<RefCell<EvalFrame>>;
+
+/// How deeply evaluation may nest before failing with
+/// `EvalError::StackOverflow`. Every nested `eval` counts, whether it comes
+/// from a call or from a form like `let` or `block`. A level takes up to
+/// about 10KB of native stack in a debug build, so this fits the usual 8MB
+/// main thread with room to spare.
+pub const DEFAULT_MAX_DEPTH: usize = 500;
+
+/// A native stack size for running deeper programs: a thread with this
+/// much stack can take a depth limit of `EVAL_STACK_MAX_DEPTH`.
+pub const EVAL_STACK_SIZE: usize = 64 * 1024 * 1024;
+
+/// The depth limit a thread with `EVAL_STACK_SIZE` of stack can take, set
+/// with `EvalContext::set_max_depth`.
+pub const EVAL_STACK_MAX_DEPTH: usize = 3000;
+
 #[derive(Clone)]
 pub struct EvalFrame {
     pub locals: HashMap<String, Value>,
+    /// Functions bound by `flet` and `labels`, which shadow global
+    /// functions and builtins within the frame.
+    pub functions: HashMap<String, Value>,
+    /// Blocks visible to `return-from`, each with an id unique to one
+    /// evaluation of the block.
+    pub blocks: HashMap<String, usize>,
+    parent: Option<Scope>,
 }
 
 #[derive(Clone)]
 pub struct EvalContext {
-    frames: LinkedList<EvalFrame>,
+    scope: Scope,
+    root: Scope,
     builtins: HashMap<String, Value>,
     functions_index: HashMap<String, Value>,
+    macros: HashMap<String, Value>,
+    /// Names of the functions currently being called, innermost last.
+    call_stack: Vec<String>,
+    /// How many calls to `eval` are in progress.
+    depth: usize,
+    max_depth: usize,
+    next_block_id: usize,
+    /// Tags of the `catch` forms being evaluated, innermost last.
+    catch_tags: Vec<Value>,
 }
 
 impl fmt::Debug for EvalFrame {
@@ -28,60 +69,210 @@ impl fmt::Debug for EvalFrame {
 
 impl EvalFrame {
     pub fn new(locals: HashMap<String, Value>) -> Self {
-        Self { locals }
+        Self {
+            locals,
+            functions: HashMap::new(),
+            blocks: HashMap::new(),
+            parent: None,
+        }
     }
 
     pub fn empty() -> Self {
         Self::new(HashMap::new())
     }
+
+    pub fn with_parent(locals: HashMap<String, Value>, parent: Scope) -> Self {
+        Self {
+            locals,
+            functions: HashMap::new(),
+            blocks: HashMap::new(),
+            parent: Some(parent),
+        }
+    }
 }
 
 impl EvalContext {
     pub fn new(root: EvalFrame) -> Self {
+        let root = Rc::new(RefCell::new(root));
         Self {
-            frames: LinkedList::from([root]),
+            scope: root.clone(),
+            root,
             builtins: create_builtin_functions_map(),
             functions_index: HashMap::new(),
+            macros: HashMap::new(),
+            call_stack: Vec::new(),
+            depth: 0,
+            max_depth: DEFAULT_MAX_DEPTH,
+            next_block_id: 0,
+            catch_tags: Vec::new(),
         }
     }
 
-    pub fn add_frame(&mut self, frame: EvalFrame) {
-        self.frames.push_front(frame);
+    pub fn new_block_id(&mut self) -> usize {
+        self.next_block_id += 1;
+        self.next_block_id
     }
 
-    pub fn pop_frame(&mut self) {
-        self.frames.pop_front();
+    /// Finds the id of the innermost block called `name` visible from the
+    /// current scope.
+    pub fn lookup_block(&self, name: &str) -> Option<usize> {
+        let mut frame = Some(self.scope.clone());
+        while let Some(current) = frame {
+            if let Some(id) = current.borrow().blocks.get(name) {
+                return Some(*id);
+            }
+            frame = current.borrow().parent.clone();
+        }
+        None
     }
 
-    pub fn add_function(&mut self, name: &String, value: &Value) {
-        self.root_mut().locals.insert(name.clone(), value.clone());
-        self.functions_index.insert(name.clone(), value.clone());
+    pub fn enter_catch(&mut self, tag: Value) {
+        self.catch_tags.push(tag);
     }
 
-    pub fn lookup_local(&self, name: &str) -> Option<Value> {
-        if let Some(function) = self.functions_index.get(name) {
-            return Some(function.clone());
+    pub fn exit_catch(&mut self) {
+        self.catch_tags.pop();
+    }
+
+    pub fn has_catch(&self, tag: &Value) -> bool {
+        self.catch_tags.contains(tag)
+    }
+
+    /// Limits how deeply evaluation may nest. Tail positions don't count,
+    /// as they are evaluated in place of the form they end.
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    /// Records one more nested evaluation, failing once the depth limit is
+    /// reached.
+    pub fn enter_eval(&mut self) -> Result<(), EvalError> {
+        if self.depth >= self.max_depth {
+            let names = self.call_stack.iter().rev().cloned().collect();
+            return Err(EvalError::StackOverflow(self.depth + 1, names));
         }
 
-        if let Some(value) = self.builtins.get(name) {
-            return Some(value.clone());
+        self.depth += 1;
+        Ok(())
+    }
+
+    pub fn exit_eval(&mut self) {
+        self.depth -= 1;
+    }
+
+    /// Records a call to `name`, for the names a stack overflow reports.
+    pub fn enter_call(&mut self, name: &str) {
+        self.call_stack.push(name.to_string());
+    }
+
+    /// Replaces the innermost call, for a call in tail position.
+    pub fn replace_call(&mut self, name: &str) {
+        if let Some(last) = self.call_stack.last_mut() {
+            *last = name.to_string();
         }
+    }
+
+    pub fn exit_call(&mut self) {
+        self.call_stack.pop();
+    }
+
+    /// Returns the innermost scope, for closures to capture.
+    pub fn current_scope(&self) -> Scope {
+        self.scope.clone()
+    }
+
+    /// Makes `scope` the innermost scope and returns the one it replaced,
+    /// to be handed back to `exit_scope`.
+    pub fn enter_scope(&mut self, scope: Scope) -> Scope {
+        std::mem::replace(&mut self.scope, scope)
+    }
+
+    pub fn exit_scope(&mut self, previous: Scope) {
+        self.scope = previous;
+    }
+
+    pub fn add_function(&mut self, name: &str, value: &Value) {
+        self.root
+            .borrow_mut()
+            .locals
+            .insert(name.to_string(), value.clone());
+        self.functions_index.insert(name.to_string(), value.clone());
+    }
+
+    /// Registers a Rust closure as a builtin. Arguments are converted with
+    /// `FromValue`, so calls with the wrong number or type of arguments fail
+    /// before the closure runs.
+    pub fn register_function<Args, F: IntoNativeFunction<Args>>(&mut self, name: &str, function: F) {
+        let function = Rc::new(function.into_native_function()) as Rc<dyn Function>;
+        self.builtins
+            .insert(name.to_string(), Value::Symbol(function));
+    }
+
+    pub fn add_macro(&mut self, name: &str, value: &Value) {
+        self.macros.insert(name.to_string(), value.clone());
+    }
 
-        for frame in self.frames.iter() {
-            if let Some(var) = frame.locals.get(name) {
-                let val = Some(var.clone());
-                return val;
+    pub fn lookup_macro(&self, name: &str) -> Option<Value> {
+        self.macros.get(name).cloned()
+    }
+
+    pub fn macro_names(&self) -> impl Iterator<Item = &String> {
+        self.macros.keys()
+    }
+
+    /// Finds what `name` refers to: the innermost binding in the scope
+    /// chain, whether a variable or a local function, then a global
+    /// function, then a builtin. Lexical bindings can therefore shadow
+    /// builtins like `list`.
+    pub fn lookup_local(&self, name: &str) -> Option<Value> {
+        let mut frame = Some(self.scope.clone());
+        while let Some(current) = frame {
+            let current = current.borrow();
+            if let Some(value) = current.functions.get(name).or_else(|| current.locals.get(name)) {
+                return Some(value.clone());
             }
+            frame = current.parent.clone();
         }
 
-        None
+        if let Some(function) = self.functions_index.get(name) {
+            return Some(function.clone());
+        }
+
+        self.builtins.get(name).cloned()
+    }
+
+    pub fn lookup_global(&self, name: &str) -> Option<Value> {
+        self.root.borrow().locals.get(name).cloned()
     }
 
-    pub fn root_mut(&mut self) -> &mut EvalFrame {
-        self.frames.back_mut().unwrap()
+    /// Defines or replaces `name` in the root scope, whatever scope is
+    /// current.
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.root
+            .borrow_mut()
+            .locals
+            .insert(name.to_string(), value);
     }
 
-    pub fn head_mut(&mut self) -> &mut EvalFrame {
-        self.frames.front_mut().unwrap()
+    /// Assigns to the innermost binding of `name`, defining it in the
+    /// innermost scope when no binding exists yet.
+    pub fn set_local(&mut self, name: &str, value: Value) {
+        let mut frame = Some(self.scope.clone());
+        while let Some(current) = frame {
+            if let Some(var) = current.borrow_mut().locals.get_mut(name) {
+                *var = value;
+                return;
+            }
+            frame = current.borrow().parent.clone();
+        }
+
+        self.scope
+            .borrow_mut()
+            .locals
+            .insert(name.to_string(), value);
     }
 }
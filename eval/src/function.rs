@@ -1,18 +1,21 @@
 use lisp::Expression;
 
 use crate::{
-    base::{eval, EvalError, EvalResult},
+    base::{eval, EvalError, EvalResult, TailStep},
     frame::EvalContext,
     value::Value,
 };
 
 pub mod builtin;
 pub mod custom;
+pub mod native;
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum ArgumentsSize {
     Exact(usize),
     Range(std::ops::RangeFrom<usize>),
+    /// For functions with optional arguments.
+    Between(std::ops::RangeInclusive<usize>),
 }
 
 impl ArgumentsSize {
@@ -20,6 +23,7 @@ impl ArgumentsSize {
         match self {
             ArgumentsSize::Exact(exact_size) => value == *exact_size,
             ArgumentsSize::Range(range) => range.contains(&value),
+            ArgumentsSize::Between(range) => range.contains(&value),
         }
     }
 }
@@ -27,25 +31,39 @@ impl ArgumentsSize {
 pub trait Function {
     fn get_arguments_size(&self) -> ArgumentsSize;
     fn eval(&self, arguments: Vec<Value>, context: &mut EvalContext) -> EvalResult;
+
+    /// Like `eval`, but may leave the function body for the caller to
+    /// evaluate, so calls in tail position run in constant stack space.
+    fn eval_tail(
+        &self,
+        arguments: Vec<Value>,
+        context: &mut EvalContext,
+    ) -> Result<TailStep, EvalError> {
+        self.eval(arguments, context).map(TailStep::Value)
+    }
 }
 
 pub fn eval_args(
     arguments: &[Expression],
     context: &mut EvalContext,
 ) -> Result<Vec<Value>, EvalError> {
-    arguments.iter().map(|arg| eval(arg, context)).collect()
+    let mut values = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        values.push(eval(argument, context)?);
+    }
+    Ok(values)
 }
 
 pub fn invoke_function(
     function: &dyn Function,
     expressions: &[Expression],
     context: &mut EvalContext,
-) -> EvalResult {
+) -> Result<TailStep, EvalError> {
     if !function.get_arguments_size().contains(expressions.len()) {
         return Err(EvalError::BadArguments);
     }
 
-    let arguments = eval_args(&expressions, context)?;
+    let arguments = eval_args(expressions, context)?;
 
-    function.eval(arguments, context)
+    function.eval_tail(arguments, context)
 }
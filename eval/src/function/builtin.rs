@@ -4,37 +4,21 @@ use std::rc::Rc;
 use crate::{function::Function, value::Value};
 
 pub mod io;
+pub mod list;
 pub mod logic;
 pub mod math;
 pub mod misc;
+pub mod string;
 
 pub use io::*;
+pub use list::*;
 pub use logic::*;
 pub use math::*;
 pub use misc::*;
+pub use string::*;
 
 pub fn create_builtin_functions_map() -> HashMap<String, Value> {
-    HashMap::from([
-        (
-            String::from("+"),
-            Value::Symbol(Rc::new(AddFunction {}) as Rc<dyn Function>),
-        ),
-        (
-            String::from("-"),
-            Value::Symbol(Rc::new(SubFunction {}) as Rc<dyn Function>),
-        ),
-        (
-            String::from("*"),
-            Value::Symbol(Rc::new(MulFunction {}) as Rc<dyn Function>),
-        ),
-        (
-            String::from("/"),
-            Value::Symbol(Rc::new(DivFunction {}) as Rc<dyn Function>),
-        ),
-        (
-            String::from("concatenate"),
-            Value::Symbol(Rc::new(ConcatenateFunction {}) as Rc<dyn Function>),
-        ),
+    let mut functions = HashMap::from([
         (
             String::from("="),
             Value::Symbol(Rc::new(EqFunction {}) as Rc<dyn Function>),
@@ -64,16 +48,72 @@ pub fn create_builtin_functions_map() -> HashMap<String, Value> {
             Value::Symbol(Rc::new(ReadFunction {}) as Rc<dyn Function>),
         ),
         (
-            String::from("and"),
-            Value::Symbol(Rc::new(AndFunction {}) as Rc<dyn Function>),
+            String::from("not"),
+            Value::Symbol(Rc::new(NotFunction {}) as Rc<dyn Function>),
         ),
         (
-            String::from("or"),
-            Value::Symbol(Rc::new(OrFunction {}) as Rc<dyn Function>),
+            String::from("cons"),
+            Value::Symbol(Rc::new(ConsFunction {}) as Rc<dyn Function>),
         ),
         (
-            String::from("not"),
-            Value::Symbol(Rc::new(NotFunction {}) as Rc<dyn Function>),
+            String::from("car"),
+            Value::Symbol(Rc::new(CarFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("cdr"),
+            Value::Symbol(Rc::new(CdrFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("list"),
+            Value::Symbol(Rc::new(ListFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("length"),
+            Value::Symbol(Rc::new(LengthFunction {}) as Rc<dyn Function>),
         ),
-    ])
+        (
+            String::from("append"),
+            Value::Symbol(Rc::new(AppendFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("reverse"),
+            Value::Symbol(Rc::new(ReverseFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("nth"),
+            Value::Symbol(Rc::new(NthFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("null"),
+            Value::Symbol(Rc::new(NullFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("consp"),
+            Value::Symbol(Rc::new(ConspFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("listp"),
+            Value::Symbol(Rc::new(ListpFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("macroexpand"),
+            Value::Symbol(Rc::new(MacroexpandFunction {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("macroexpand-1"),
+            Value::Symbol(Rc::new(Macroexpand1Function {}) as Rc<dyn Function>),
+        ),
+        (
+            String::from("error"),
+            Value::Symbol(Rc::new(ErrorFunction {}) as Rc<dyn Function>),
+        ),
+    ]);
+    let natives = math_functions().into_iter().chain(string_functions());
+    functions.extend(natives.map(|(name, function)| {
+        (
+            String::from(name),
+            Value::Symbol(Rc::new(function) as Rc<dyn Function>),
+        )
+    }));
+    functions
 }
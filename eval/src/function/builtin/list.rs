@@ -0,0 +1,187 @@
+use lisp::Literal;
+
+use crate::{
+    base::{EvalError, EvalResult},
+    frame::EvalContext,
+    function::{ArgumentsSize, Function},
+    value::Value,
+};
+
+pub struct ConsFunction {}
+pub struct CarFunction {}
+pub struct CdrFunction {}
+pub struct ListFunction {}
+pub struct LengthFunction {}
+pub struct AppendFunction {}
+pub struct ReverseFunction {}
+pub struct NthFunction {}
+pub struct NullFunction {}
+pub struct ConspFunction {}
+pub struct ListpFunction {}
+
+fn _to_list(value: &Value) -> Result<Vec<Value>, EvalError> {
+    value.to_vec().ok_or(EvalError::BadArguments)
+}
+
+fn _from_bool(value: bool) -> Value {
+    if value {
+        Value::Literal(Literal::True)
+    } else {
+        Value::Literal(Literal::Nil)
+    }
+}
+
+impl Function for ConsFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(2)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        match &arguments[..] {
+            [car, cdr] => Ok(Value::cons(car.clone(), cdr.clone())),
+            _ => Err(EvalError::BadArguments),
+        }
+    }
+}
+
+impl Function for CarFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        match &arguments[..] {
+            [Value::Cons(cons)] => Ok(cons.car.clone()),
+            [Value::Literal(Literal::Nil)] => Ok(Value::Literal(Literal::Nil)),
+            _ => Err(EvalError::BadArguments),
+        }
+    }
+}
+
+impl Function for CdrFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        match &arguments[..] {
+            [Value::Cons(cons)] => Ok(cons.cdr.clone()),
+            [Value::Literal(Literal::Nil)] => Ok(Value::Literal(Literal::Nil)),
+            _ => Err(EvalError::BadArguments),
+        }
+    }
+}
+
+impl Function for ListFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Range(0..)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        Ok(Value::list(arguments))
+    }
+}
+
+impl Function for LengthFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        let values = _to_list(&arguments[0])?;
+        Ok(Value::Literal(Literal::Integer(values.len() as i64)))
+    }
+}
+
+impl Function for AppendFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Range(0..)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        match &arguments[..] {
+            [] => Ok(Value::Literal(Literal::Nil)),
+            [lists @ .., tail] => {
+                let mut values = vec![];
+                for list in lists {
+                    values.extend(_to_list(list)?);
+                }
+                Ok(Value::list_with_tail(values, tail.clone()))
+            }
+        }
+    }
+}
+
+impl Function for ReverseFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        let mut values = _to_list(&arguments[0])?;
+        values.reverse();
+        Ok(Value::list(values))
+    }
+}
+
+impl Function for NthFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(2)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        match &arguments[..] {
+            [Value::Literal(Literal::Integer(index)), list] if *index >= 0 => {
+                let mut rest = list;
+                for _ in 0..(*index as usize) {
+                    match rest {
+                        Value::Cons(cons) => rest = &cons.cdr,
+                        _ => break,
+                    }
+                }
+                match rest {
+                    Value::Cons(cons) => Ok(cons.car.clone()),
+                    Value::Literal(Literal::Nil) => Ok(Value::Literal(Literal::Nil)),
+                    _ => Err(EvalError::BadArguments),
+                }
+            }
+            _ => Err(EvalError::BadArguments),
+        }
+    }
+}
+
+impl Function for NullFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        Ok(_from_bool(matches!(
+            arguments[0],
+            Value::Literal(Literal::Nil)
+        )))
+    }
+}
+
+impl Function for ConspFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        Ok(_from_bool(matches!(arguments[0], Value::Cons(_))))
+    }
+}
+
+impl Function for ListpFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        Ok(_from_bool(matches!(
+            arguments[0],
+            Value::Cons(_) | Value::Literal(Literal::Nil)
+        )))
+    }
+}
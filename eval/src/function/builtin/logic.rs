@@ -1,9 +1,13 @@
+use std::cmp::Ordering;
+
 use lisp::Literal;
 
 use crate::{
     base::{EvalError, EvalResult},
+    convert::{FromValue, IntoValue},
     frame::EvalContext,
     function::{ArgumentsSize, Function},
+    number::Number,
     value::Value,
 };
 
@@ -18,13 +22,24 @@ pub struct GreaterEqFunction {}
 #[derive(Debug)]
 pub struct LessEqFunction {}
 
-#[derive(Debug)]
-pub struct AndFunction {}
-#[derive(Debug)]
-pub struct OrFunction {}
 #[derive(Debug)]
 pub struct NotFunction {}
 
+/// Numbers are equal by value whatever their type, other values when
+/// they are the same.
+fn _equal(left: &Value, right: &Value) -> bool {
+    match (Number::from_value(left.clone()), Number::from_value(right.clone())) {
+        (Ok(left), Ok(right)) => left.compare(&right) == Some(Ordering::Equal),
+        _ => left == right,
+    }
+}
+
+fn _compare(arguments: Vec<Value>, predicate: fn(Ordering) -> bool) -> EvalResult {
+    let left = Number::from_value(arguments[0].clone())?;
+    let right = Number::from_value(arguments[1].clone())?;
+    Ok(left.compare(&right).is_some_and(predicate).into_value())
+}
+
 impl Function for EqFunction {
     fn get_arguments_size(&self) -> ArgumentsSize {
         ArgumentsSize::Range(2..)
@@ -33,7 +48,7 @@ impl Function for EqFunction {
     fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
         let value = arguments[1..]
             .iter()
-            .all(|item| item == arguments.first().unwrap());
+            .all(|item| _equal(item, arguments.first().unwrap()));
         Ok(if value {
             Value::Literal(Literal::True)
         } else {
@@ -48,16 +63,7 @@ impl Function for GreaterFunction {
     }
 
     fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(left)), Value::Literal(Literal::Number(right))] => {
-                Ok(if left > right {
-                    Value::Literal(Literal::True)
-                } else {
-                    Value::Literal(Literal::Nil)
-                })
-            }
-            _ => Err(EvalError::BadArguments),
-        }
+        _compare(arguments, Ordering::is_gt)
     }
 }
 
@@ -67,16 +73,7 @@ impl Function for LessFunction {
     }
 
     fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(left)), Value::Literal(Literal::Number(right))] => {
-                Ok(if left < right {
-                    Value::Literal(Literal::True)
-                } else {
-                    Value::Literal(Literal::Nil)
-                })
-            }
-            _ => Err(EvalError::BadArguments),
-        }
+        _compare(arguments, Ordering::is_lt)
     }
 }
 
@@ -86,16 +83,7 @@ impl Function for GreaterEqFunction {
     }
 
     fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(left)), Value::Literal(Literal::Number(right))] => {
-                Ok(if left >= right {
-                    Value::Literal(Literal::True)
-                } else {
-                    Value::Literal(Literal::Nil)
-                })
-            }
-            _ => Err(EvalError::BadArguments),
-        }
+        _compare(arguments, Ordering::is_ge)
     }
 }
 
@@ -105,50 +93,7 @@ impl Function for LessEqFunction {
     }
 
     fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(left)), Value::Literal(Literal::Number(right))] => {
-                Ok(if left <= right {
-                    Value::Literal(Literal::True)
-                } else {
-                    Value::Literal(Literal::Nil)
-                })
-            }
-            _ => Err(EvalError::BadArguments),
-        }
-    }
-}
-
-impl Function for AndFunction {
-    fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(2..)
-    }
-
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
-        if arguments
-            .into_iter()
-            .any(|arg| arg == Value::Literal(Literal::Nil))
-        {
-            Ok(Value::Literal(Literal::Nil))
-        } else {
-            Ok(Value::Literal(Literal::True))
-        }
-    }
-}
-
-impl Function for OrFunction {
-    fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(2..)
-    }
-
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
-        if arguments
-            .into_iter()
-            .any(|arg| arg == Value::Literal(Literal::True))
-        {
-            Ok(Value::Literal(Literal::True))
-        } else {
-            Ok(Value::Literal(Literal::Nil))
-        }
+        _compare(arguments, Ordering::is_le)
     }
 }
 
@@ -159,8 +104,8 @@ impl Function for NotFunction {
 
     fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
         match &arguments[..] {
-            [Value::Literal(Literal::Nil)] => Ok(Value::Literal(Literal::True)),
-            [_] => Ok(Value::Literal(Literal::Nil)),
+            [value] if value.is_true() => Ok(Value::Literal(Literal::Nil)),
+            [_] => Ok(Value::Literal(Literal::True)),
             _ => Err(EvalError::UndefinedBehaviour),
         }
     }
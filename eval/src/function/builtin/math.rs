@@ -1,129 +1,259 @@
-use lisp::Literal;
+use std::cmp::Ordering;
+
+use lisp::{BigInt, Literal};
+use num_integer::Integer;
 
 use crate::{
-    base::EvalError,
-    frame::EvalContext,
-    function::{ArgumentsSize, Function},
+    base::{EvalError, EvalResult},
+    convert::{FromValue, IntoValue},
+    function::{
+        native::{IntoNativeFunction, NativeFunction},
+        ArgumentsSize,
+    },
+    number::{Number, Rounding},
     value::Value,
 };
 
-pub struct AddFunction {}
-pub struct SubFunction {}
-pub struct MulFunction {}
-pub struct DivFunction {}
-
-impl Function for AddFunction {
-    fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(2..)
-    }
+type Operation = fn(Number, Number) -> Result<Number, EvalError>;
 
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(first)), rest @ ..] => {
-                if rest.iter().any(|value| match value {
-                    Value::Literal(Literal::Number(_)) => false,
-                    _ => true,
-                }) {
-                    return Err(EvalError::UndefinedBehaviour);
-                }
+/// A builtin taking a varying number of numbers, which are checked and
+/// converted before `callback` runs.
+fn _numeric(arguments_size: ArgumentsSize, callback: fn(Vec<Number>) -> EvalResult) -> NativeFunction {
+    NativeFunction::new(arguments_size, move |arguments| {
+        let numbers = arguments
+            .into_iter()
+            .map(Number::from_value)
+            .collect::<Result<Vec<Number>, EvalError>>()?;
+        callback(numbers)
+    })
+}
 
-                Ok(Value::Literal(Literal::Number(
-                    rest.iter()
-                        .filter_map(|value| match value {
-                            Value::Literal(Literal::Number(n)) => Some(*n),
-                            _ => None,
-                        })
-                        .fold(*first, |acc, val| acc + val),
-                )))
-            }
-            _ => Err(EvalError::UndefinedBehaviour),
-        }
+/// Folds the numbers from the left with `operation`. A single number is
+/// combined with `identity` first, so `(- x)` negates and `(/ x)` inverts.
+fn _fold(mut numbers: Vec<Number>, identity: Number, operation: Operation) -> EvalResult {
+    if numbers.len() == 1 {
+        numbers.insert(0, identity.clone());
     }
+    let mut numbers = numbers.into_iter();
+    let first = numbers.next().unwrap_or(identity);
+    Ok(numbers.try_fold(first, operation)?.into_value())
 }
 
-impl Function for SubFunction {
-    fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(2..)
-    }
+/// Divides the first number by the second, 1 by default, and rounds the
+/// quotient to an integer.
+fn _quotient(numbers: Vec<Number>, rounding: Rounding) -> EvalResult {
+    let mut numbers = numbers.into_iter();
+    let number = numbers.next().ok_or(EvalError::BadArguments)?;
+    let divisor = numbers.next().unwrap_or(Number::Integer(1));
+    Ok(number.quotient(divisor, rounding)?.into_value())
+}
 
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(first)), rest @ ..] => {
-                if rest.iter().any(|value| match value {
-                    Value::Literal(Literal::Number(_)) => false,
-                    _ => true,
-                }) {
-                    return Err(EvalError::UndefinedBehaviour);
-                }
+/// Returns the first number that compares as `wanted` against all others.
+fn _extreme(numbers: Vec<Number>, wanted: Ordering) -> EvalResult {
+    let mut numbers = numbers.into_iter();
+    let first = numbers.next().ok_or(EvalError::BadArguments)?;
+    let extreme = numbers.fold(first, |extreme, number| match number.compare(&extreme) {
+        Some(ordering) if ordering == wanted => number,
+        _ => extreme,
+    });
+    Ok(extreme.into_value())
+}
 
-                Ok(Value::Literal(Literal::Number(
-                    rest.iter()
-                        .filter_map(|value| match value {
-                            Value::Literal(Literal::Number(n)) => Some(*n),
-                            _ => None,
-                        })
-                        .fold(*first, |acc, val| acc - val),
-                )))
-            }
-            _ => Err(EvalError::UndefinedBehaviour),
-        }
+fn _integer(number: Number) -> Result<BigInt, EvalError> {
+    match number {
+        Number::Integer(integer) => Ok(BigInt::from(integer)),
+        Number::BigInteger(integer) => Ok(integer),
+        number => Err(EvalError::TypeMismatch("integer", number.into_value())),
     }
 }
 
-impl Function for MulFunction {
-    fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(2..)
+/// Folds integers with `operation`, starting from `identity`.
+fn _fold_integers(
+    numbers: Vec<Number>,
+    identity: i64,
+    operation: fn(&BigInt, &BigInt) -> BigInt,
+) -> EvalResult {
+    let mut result = BigInt::from(identity);
+    for number in numbers {
+        result = operation(&result, &_integer(number)?);
     }
+    Ok(Value::Literal(Literal::integer(result)))
+}
 
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(first)), rest @ ..] => {
-                if rest.iter().any(|value| match value {
-                    Value::Literal(Literal::Number(_)) => false,
-                    _ => true,
-                }) {
-                    return Err(EvalError::UndefinedBehaviour);
-                }
-
-                Ok(Value::Literal(Literal::Number(
-                    rest.iter()
-                        .filter_map(|value| match value {
-                            Value::Literal(Literal::Number(n)) => Some(*n),
-                            _ => None,
-                        })
-                        .fold(*first, |acc, val| acc * val),
-                )))
-            }
-            _ => Err(EvalError::UndefinedBehaviour),
-        }
+fn _non_negative(number: Number) -> Result<f64, EvalError> {
+    match number.to_f64() {
+        float if float >= 0.0 => Ok(float),
+        _ => Err(EvalError::TypeMismatch("non-negative number", number.into_value())),
     }
 }
 
-impl Function for DivFunction {
-    fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(2..)
+/// The natural logarithm, which is only real for positive numbers.
+fn _ln(number: Number) -> Result<f64, EvalError> {
+    match number.is_zero() {
+        true => Err(EvalError::DivisionByZero),
+        false => Ok(_non_negative(number)?.ln()),
     }
+}
 
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [Value::Literal(Literal::Number(first)), rest @ ..] => {
-                if rest.iter().any(|value| match value {
-                    Value::Literal(Literal::Number(_)) => false,
-                    _ => true,
-                }) {
-                    return Err(EvalError::UndefinedBehaviour);
-                }
+/// `(log x)` is the natural logarithm, `(log x base)` the one in `base`.
+fn _log(numbers: Vec<Number>) -> EvalResult {
+    let mut numbers = numbers.into_iter();
+    let log = Number::Float(_ln(numbers.next().ok_or(EvalError::BadArguments)?)?);
+    let result = match numbers.next() {
+        Some(base) => log.checked_div(Number::Float(_ln(base)?))?,
+        None => log,
+    };
+    Ok(result.into_value())
+}
 
-                Ok(Value::Literal(Literal::Number(
-                    rest.iter()
-                        .filter_map(|value| match value {
-                            Value::Literal(Literal::Number(n)) => Some(*n),
-                            _ => None,
-                        })
-                        .fold(*first, |acc, val| acc * val),
-                )))
-            }
-            _ => Err(EvalError::UndefinedBehaviour),
-        }
-    }
+/// `(atan y)` is the arc tangent of `y`, `(atan y x)` the angle of the
+/// point `(x, y)`.
+fn _atan(numbers: Vec<Number>) -> EvalResult {
+    let angle = match &numbers[..] {
+        [y] => y.to_f64().atan(),
+        [y, x] => y.to_f64().atan2(x.to_f64()),
+        _ => return Err(EvalError::BadArguments),
+    };
+    Ok(Number::Float(angle).into_value())
+}
+
+/// The numeric builtins, named as in Common Lisp.
+pub fn math_functions() -> Vec<(&'static str, NativeFunction)> {
+    vec![
+        (
+            "+",
+            _numeric(ArgumentsSize::Range(0..), |numbers| {
+                _fold(numbers, Number::Integer(0), |left, right| Ok(left + right))
+            }),
+        ),
+        (
+            "-",
+            _numeric(ArgumentsSize::Range(1..), |numbers| {
+                _fold(numbers, Number::Integer(0), |left, right| Ok(left - right))
+            }),
+        ),
+        (
+            "*",
+            _numeric(ArgumentsSize::Range(0..), |numbers| {
+                _fold(numbers, Number::Integer(1), |left, right| Ok(left * right))
+            }),
+        ),
+        (
+            "/",
+            _numeric(ArgumentsSize::Range(1..), |numbers| {
+                _fold(numbers, Number::Integer(1), Number::checked_div)
+            }),
+        ),
+        (
+            "truncate",
+            _numeric(ArgumentsSize::Between(1..=2), |numbers| {
+                _quotient(numbers, Rounding::Truncate)
+            }),
+        ),
+        (
+            "floor",
+            _numeric(ArgumentsSize::Between(1..=2), |numbers| {
+                _quotient(numbers, Rounding::Floor)
+            }),
+        ),
+        (
+            "round",
+            _numeric(ArgumentsSize::Between(1..=2), |numbers| {
+                _quotient(numbers, Rounding::Nearest)
+            }),
+        ),
+        (
+            "mod",
+            (|number: Number, divisor: Number| number.remainder(divisor, Rounding::Floor))
+                .into_native_function(),
+        ),
+        (
+            "rem",
+            (|number: Number, divisor: Number| number.remainder(divisor, Rounding::Truncate))
+                .into_native_function(),
+        ),
+        (
+            "expt",
+            (|base: Number, power: Number| base.pow(power)).into_native_function(),
+        ),
+        (
+            "sqrt",
+            (|number: Number| Ok(Number::Float(_non_negative(number)?.sqrt())))
+                .into_native_function(),
+        ),
+        (
+            "exp",
+            (|number: Number| Number::Float(number.to_f64().exp())).into_native_function(),
+        ),
+        ("log", _numeric(ArgumentsSize::Between(1..=2), _log)),
+        (
+            "sin",
+            (|number: Number| Number::Float(number.to_f64().sin())).into_native_function(),
+        ),
+        (
+            "cos",
+            (|number: Number| Number::Float(number.to_f64().cos())).into_native_function(),
+        ),
+        (
+            "tan",
+            (|number: Number| Number::Float(number.to_f64().tan())).into_native_function(),
+        ),
+        ("atan", _numeric(ArgumentsSize::Between(1..=2), _atan)),
+        ("abs", (|number: Number| number.abs()).into_native_function()),
+        (
+            "min",
+            _numeric(ArgumentsSize::Range(1..), |numbers| _extreme(numbers, Ordering::Less)),
+        ),
+        (
+            "max",
+            _numeric(ArgumentsSize::Range(1..), |numbers| _extreme(numbers, Ordering::Greater)),
+        ),
+        (
+            "1+",
+            (|number: Number| number + Number::Integer(1)).into_native_function(),
+        ),
+        (
+            "1-",
+            (|number: Number| number - Number::Integer(1)).into_native_function(),
+        ),
+        ("zerop", (|number: Number| number.is_zero()).into_native_function()),
+        (
+            "evenp",
+            (|number: Number| _integer(number).map(|integer| integer.is_even()))
+                .into_native_function(),
+        ),
+        (
+            "oddp",
+            (|number: Number| _integer(number).map(|integer| integer.is_odd()))
+                .into_native_function(),
+        ),
+        (
+            "gcd",
+            _numeric(ArgumentsSize::Range(0..), |numbers| {
+                _fold_integers(numbers, 0, Integer::gcd)
+            }),
+        ),
+        (
+            "lcm",
+            _numeric(ArgumentsSize::Range(0..), |numbers| {
+                _fold_integers(numbers, 1, Integer::lcm)
+            }),
+        ),
+        (
+            "integerp",
+            (|value: Value| {
+                matches!(value, Value::Literal(Literal::Integer(_) | Literal::BigInteger(_)))
+            })
+            .into_native_function(),
+        ),
+        (
+            "floatp",
+            (|value: Value| matches!(value, Value::Literal(Literal::Float(_))))
+                .into_native_function(),
+        ),
+        (
+            "float",
+            (|number: Number| Number::Float(number.to_f64())).into_native_function(),
+        ),
+    ]
 }
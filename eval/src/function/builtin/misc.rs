@@ -1,25 +1,42 @@
-use lisp::Literal;
-
 use crate::{
-    base::EvalError,
+    base::{EvalError, EvalResult},
+    expand::{expand_macro, expand_macro_1},
     frame::EvalContext,
     function::{ArgumentsSize, Function},
     value::Value,
 };
 
-pub struct ConcatenateFunction {}
+pub struct MacroexpandFunction {}
+pub struct Macroexpand1Function {}
+pub struct ErrorFunction {}
+
+impl Function for MacroexpandFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, context: &mut EvalContext) -> EvalResult {
+        expand_macro(&arguments[0], context)
+    }
+}
+
+impl Function for Macroexpand1Function {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        ArgumentsSize::Exact(1)
+    }
+
+    fn eval(&self, arguments: Vec<Value>, context: &mut EvalContext) -> EvalResult {
+        let form = &arguments[0];
+        Ok(expand_macro_1(form, context)?.unwrap_or_else(|| form.clone()))
+    }
+}
 
-impl Function for ConcatenateFunction {
+impl Function for ErrorFunction {
     fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Range(1..)
+        ArgumentsSize::Exact(1)
     }
 
-    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> Result<Value, EvalError> {
-        match &arguments[..] {
-            [_, ..] => Ok(Value::Literal(Literal::String(
-                arguments.iter().map(Value::to_string).collect(),
-            ))),
-            _ => Err(EvalError::UndefinedBehaviour),
-        }
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        Err(EvalError::User(arguments[0].clone()))
     }
 }
@@ -0,0 +1,173 @@
+use compiler::lex::parse_number;
+use lisp::Literal;
+
+use crate::{
+    base::{EvalError, EvalResult},
+    convert::{FromValue, IntoValue},
+    function::{
+        native::{IntoNativeFunction, NativeFunction},
+        ArgumentsSize,
+    },
+    number::Number,
+    value::Value,
+};
+
+/// The contents of a string, or the printed form of anything else.
+fn _text(value: &Value) -> String {
+    match value {
+        Value::Literal(Literal::String(string)) => string.clone(),
+        value => value.to_string(),
+    }
+}
+
+fn _string(value: &Value) -> Result<String, EvalError> {
+    String::from_value(value.clone())
+}
+
+/// Checks that a character index is within `0..=length`.
+fn _index(index: i64, length: usize) -> Result<usize, EvalError> {
+    match usize::try_from(index) {
+        Ok(index) if index <= length => Ok(index),
+        _ => Err(EvalError::IndexOutOfBounds(index, length)),
+    }
+}
+
+/// `(subseq string start [end])`, counting in characters.
+fn _subseq(arguments: Vec<Value>) -> EvalResult {
+    let (string, start, end) = match &arguments[..] {
+        [string, start] => (string, start, &Value::Literal(Literal::Nil)),
+        [string, start, end] => (string, start, end),
+        _ => return Err(EvalError::BadArguments),
+    };
+
+    let chars = _string(string)?.chars().collect::<Vec<char>>();
+    let start = _index(i64::from_value(start.clone())?, chars.len())?;
+    let end = match Option::<i64>::from_value(end.clone())? {
+        Some(end) => _index(end, chars.len())?,
+        None => chars.len(),
+    };
+    if start > end {
+        return Err(EvalError::InvalidRange(start, end));
+    }
+    Ok(chars[start..end].iter().collect::<String>().into_value())
+}
+
+/// `(string-trim string)` trims whitespace, `(string-trim bag string)` the
+/// characters in `bag`.
+fn _string_trim(arguments: Vec<Value>) -> EvalResult {
+    let trimmed = match &arguments[..] {
+        [string] => _string(string)?.trim().to_string(),
+        [bag, string] => {
+            let bag = _string(bag)?.chars().collect::<Vec<char>>();
+            _string(string)?.trim_matches(|c| bag.contains(&c)).to_string()
+        }
+        _ => return Err(EvalError::BadArguments),
+    };
+    Ok(trimmed.into_value())
+}
+
+/// `(split string [separator])` splits on whitespace by default, and into
+/// characters with an empty separator.
+fn _split(arguments: Vec<Value>) -> EvalResult {
+    let parts = match &arguments[..] {
+        [string] => _string(string)?.split_whitespace().map(String::from).collect(),
+        [string, separator] => {
+            let (string, separator) = (_string(string)?, _string(separator)?);
+            match separator.is_empty() {
+                true => string.chars().map(String::from).collect(),
+                false => string.split(&separator).map(String::from).collect(),
+            }
+        }
+        _ => return Err(EvalError::BadArguments),
+    };
+    Ok(Vec::<String>::into_value(parts))
+}
+
+/// `(join list [separator])` joins with a space by default.
+fn _join(arguments: Vec<Value>) -> EvalResult {
+    let (list, separator) = match &arguments[..] {
+        [list] => (list, String::from(" ")),
+        [list, separator] => (list, _string(separator)?),
+        _ => return Err(EvalError::BadArguments),
+    };
+    let parts = Vec::<Value>::from_value(list.clone())?;
+    Ok(parts.iter().map(_text).collect::<Vec<String>>().join(&separator).into_value())
+}
+
+/// The string builtins. Strings are handled as their contents rather than
+/// their printed form, and indices and lengths count characters.
+pub fn string_functions() -> Vec<(&'static str, NativeFunction)> {
+    vec![
+        (
+            "concatenate",
+            NativeFunction::new(ArgumentsSize::Range(1..), |arguments| {
+                Ok(arguments.iter().map(_text).collect::<String>().into_value())
+            }),
+        ),
+        (
+            "string-length",
+            (|string: String| string.chars().count() as i64).into_native_function(),
+        ),
+        ("subseq", NativeFunction::new(ArgumentsSize::Between(2..=3), _subseq)),
+        (
+            "string-upcase",
+            (|string: String| string.to_uppercase()).into_native_function(),
+        ),
+        (
+            "string-downcase",
+            (|string: String| string.to_lowercase()).into_native_function(),
+        ),
+        (
+            "string-trim",
+            NativeFunction::new(ArgumentsSize::Between(1..=2), _string_trim),
+        ),
+        ("split", NativeFunction::new(ArgumentsSize::Between(1..=2), _split)),
+        ("join", NativeFunction::new(ArgumentsSize::Between(1..=2), _join)),
+        (
+            "search",
+            (|needle: String, string: String| {
+                string
+                    .find(&needle)
+                    .map(|offset| string[..offset].chars().count() as i64)
+            })
+            .into_native_function(),
+        ),
+        (
+            "string=",
+            (|left: String, right: String| left == right).into_native_function(),
+        ),
+        (
+            "string<",
+            (|left: String, right: String| left < right).into_native_function(),
+        ),
+        (
+            "parse-number",
+            (|string: String| parse_number(string.trim()).map(Value::Literal))
+                .into_native_function(),
+        ),
+        (
+            "number-to-string",
+            (|number: Number| number.into_value().to_string()).into_native_function(),
+        ),
+        (
+            "char",
+            (|string: String, index: i64| {
+                let length = string.chars().count();
+                usize::try_from(index)
+                    .ok()
+                    .and_then(|index| string.chars().nth(index))
+                    .map(String::from)
+                    .ok_or(EvalError::IndexOutOfBounds(index, length))
+            })
+            .into_native_function(),
+        ),
+        (
+            "string-replace",
+            (|string: String, old: String, new: String| match old.is_empty() {
+                true => Err(EvalError::TypeMismatch("non-empty string", old.into_value())),
+                false => Ok(string.replace(&old, &new)),
+            })
+            .into_native_function(),
+        ),
+    ]
+}
@@ -1,42 +1,156 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::{Rc, Weak};
+
 use crate::{
-    base::EvalError,
-    frame::{EvalContext, EvalFrame},
+    base::{EvalError, TailStep},
+    frame::{EvalContext, EvalFrame, Scope},
     function::{ArgumentsSize, Function},
     value::Value,
 };
-use lisp::Expression;
+use lisp::{Expression, LocalFunction};
 
 use super::eval;
 
+/// The scope a function closes over.
+enum Closure {
+    Scope(Scope),
+    /// The frame of the `labels` form that defined the function. It is held
+    /// weakly, as the frame holds the function. A function that outlives
+    /// the form rebuilds the frame from `outer` and its `siblings`.
+    Labels {
+        frame: Weak<RefCell<EvalFrame>>,
+        outer: Scope,
+        siblings: Rc<[LocalFunction]>,
+    },
+}
+
 pub struct CustomFunction {
+    /// The name the function was defined with, `lambda` for anonymous
+    /// functions.
+    name: String,
+    /// Whether the body runs in an implicit block named after the function,
+    /// as for `defun` but not `lambda`.
+    has_block: bool,
     parameter_names: Vec<String>,
+    rest_name: Option<String>,
     code: Expression,
+    scope: Closure,
 }
 
 impl CustomFunction {
-    pub fn new(parameters: Vec<String>, code: Expression) -> Self {
+    pub fn new(name: &str, parameters: Vec<String>, code: Expression, scope: Scope) -> Self {
+        let mut parameter_names = parameters;
+        let mut rest_name = None;
+        if let Some(index) = parameter_names
+            .iter()
+            .position(|name| name == "&rest" || name == "&body")
+        {
+            rest_name = parameter_names.get(index + 1).cloned();
+            parameter_names.truncate(index);
+        }
+
         Self {
-            parameter_names: parameters,
+            name: name.to_string(),
+            has_block: true,
+            parameter_names,
+            rest_name,
             code,
+            scope: Closure::Scope(scope),
+        }
+    }
+
+    pub fn lambda(parameters: Vec<String>, code: Expression, scope: Scope) -> Self {
+        Self {
+            has_block: false,
+            ..Self::new("lambda", parameters, code, scope)
+        }
+    }
+
+    fn _closure_scope(&self) -> Scope {
+        match &self.scope {
+            Closure::Scope(scope) => scope.clone(),
+            Closure::Labels { frame, outer, siblings } => frame
+                .upgrade()
+                .unwrap_or_else(|| bind_labels(siblings.clone(), outer.clone())),
         }
     }
+
+    /// Creates the frame binding `arguments` to the parameters, along with
+    /// the function's implicit block.
+    fn _bind_arguments(&self, arguments: Vec<Value>, context: &mut EvalContext) -> Scope {
+        let mut arguments = arguments;
+        let rest = arguments.split_off(self.parameter_names.len().min(arguments.len()));
+        let mut locals: HashMap<String, Value> = self
+            .parameter_names
+            .iter()
+            .map(Clone::clone)
+            .zip(arguments)
+            .collect();
+        if let Some(rest_name) = &self.rest_name {
+            locals.insert(rest_name.clone(), Value::list(rest));
+        }
+
+        let mut frame = EvalFrame::with_parent(locals, self._closure_scope());
+        if self.has_block {
+            frame.blocks.insert(self.name.clone(), context.new_block_id());
+        }
+        Rc::new(RefCell::new(frame))
+    }
+}
+
+/// Binds `functions` in a new frame under `outer`, each closing over that
+/// frame so they can call each other, as `labels` does.
+pub fn bind_labels(functions: Rc<[LocalFunction]>, outer: Scope) -> Scope {
+    let scope = Rc::new(RefCell::new(EvalFrame::with_parent(HashMap::new(), outer.clone())));
+    for (name, parameters, code) in functions.iter() {
+        let function = CustomFunction {
+            scope: Closure::Labels {
+                frame: Rc::downgrade(&scope),
+                outer: outer.clone(),
+                siblings: functions.clone(),
+            },
+            ..CustomFunction::new(name, parameters.clone(), code.clone(), outer.clone())
+        };
+        scope
+            .borrow_mut()
+            .functions
+            .insert(name.clone(), Value::Symbol(Rc::new(function)));
+    }
+    scope
 }
 
 impl Function for CustomFunction {
     fn get_arguments_size(&self) -> ArgumentsSize {
-        ArgumentsSize::Exact(self.parameter_names.len())
+        match self.rest_name {
+            Some(_) => ArgumentsSize::Range(self.parameter_names.len()..),
+            None => ArgumentsSize::Exact(self.parameter_names.len()),
+        }
     }
 
     fn eval(&self, arguments: Vec<Value>, context: &mut EvalContext) -> Result<Value, EvalError> {
-        context.add_frame(EvalFrame::new(
-            self.parameter_names
-                .iter()
-                .map(Clone::clone)
-                .zip(arguments.into_iter())
-                .collect(),
-        ));
+        context.enter_call(&self.name);
+        let scope = self._bind_arguments(arguments, context);
+        let block = scope.borrow().blocks.get(&self.name).copied();
+        let previous = context.enter_scope(scope);
         let result = eval(&self.code, context);
-        context.pop_frame();
-        result
+        context.exit_scope(previous);
+        context.exit_call();
+        match result {
+            Err(error) => error.returned_from(block),
+            result => result,
+        }
+    }
+
+    fn eval_tail(
+        &self,
+        arguments: Vec<Value>,
+        context: &mut EvalContext,
+    ) -> Result<TailStep, EvalError> {
+        Ok(TailStep::Call(
+            self.name.clone(),
+            self._bind_arguments(arguments, context),
+            self.code.clone(),
+        ))
     }
 }
@@ -0,0 +1,72 @@
+use crate::{
+    base::EvalResult,
+    convert::{FromValue, IntoEvalResult},
+    frame::EvalContext,
+    function::{ArgumentsSize, Function},
+    value::Value,
+};
+
+type Callback = Box<dyn Fn(Vec<Value>) -> EvalResult>;
+
+/// A function implemented by a Rust closure, see
+/// `EvalContext::register_function`.
+pub struct NativeFunction {
+    arguments_size: ArgumentsSize,
+    callback: Callback,
+}
+
+impl NativeFunction {
+    /// Wraps a closure taking the raw arguments, for functions that
+    /// `IntoNativeFunction` can't describe, such as variadic ones.
+    pub fn new(arguments_size: ArgumentsSize, callback: impl Fn(Vec<Value>) -> EvalResult + 'static) -> Self {
+        Self {
+            arguments_size,
+            callback: Box::new(callback),
+        }
+    }
+}
+
+impl Function for NativeFunction {
+    fn get_arguments_size(&self) -> ArgumentsSize {
+        self.arguments_size.clone()
+    }
+
+    fn eval(&self, arguments: Vec<Value>, _context: &mut EvalContext) -> EvalResult {
+        (self.callback)(arguments)
+    }
+}
+
+/// Implemented for closures whose parameters are all `FromValue` and whose
+/// result is `IntoEvalResult`. `Args` only tells the implementations for
+/// different arities apart.
+pub trait IntoNativeFunction<Args> {
+    fn into_native_function(self) -> NativeFunction;
+}
+
+macro_rules! impl_into_native_function {
+    ($size:expr $(, $arg:ident)*) => {
+        impl<Func, Res, $($arg,)*> IntoNativeFunction<($($arg,)*)> for Func
+        where
+            Func: Fn($($arg),*) -> Res + 'static,
+            Res: IntoEvalResult,
+            $($arg: FromValue,)*
+        {
+            #[allow(non_snake_case, unused_mut, unused_variables)]
+            fn into_native_function(self) -> NativeFunction {
+                NativeFunction::new(ArgumentsSize::Exact($size), move |arguments| {
+                    let mut arguments = arguments.into_iter();
+                    $(let $arg = $arg::from_value(arguments.next().unwrap())?;)*
+                    (self)($($arg),*).into_eval_result()
+                })
+            }
+        }
+    };
+}
+
+impl_into_native_function!(0);
+impl_into_native_function!(1, A);
+impl_into_native_function!(2, A, B);
+impl_into_native_function!(3, A, B, C);
+impl_into_native_function!(4, A, B, C, D);
+impl_into_native_function!(5, A, B, C, D, E);
+impl_into_native_function!(6, A, B, C, D, E, F);
@@ -0,0 +1,151 @@
+use std::fmt;
+use std::fmt::Write;
+
+use compiler::diagnostic;
+use compiler::lex::{self, LexError};
+use compiler::parse::{self, ParseContext, ParseError};
+use lisp::{Literal, Span};
+
+use crate::{
+    base::{eval, EvalError, TraceFrame},
+    frame::{EvalContext, EvalFrame},
+    function::native::IntoNativeFunction,
+    value::Value,
+};
+
+#[derive(Debug)]
+pub enum InterpreterError {
+    Lex(LexError),
+    Parse(ParseError),
+    Eval(EvalError),
+}
+
+impl InterpreterError {
+    pub fn span(&self) -> Option<Span> {
+        match self {
+            InterpreterError::Lex(err) => Some(err.span()),
+            InterpreterError::Parse(err) => Some(err.span()),
+            InterpreterError::Eval(err) => err.span(),
+        }
+    }
+
+    /// Renders the error against the `source` it came from. `origin` names
+    /// where the source came from, such as a file path. The caret is left
+    /// out when the span points into a different source than `source_id`.
+    pub fn render(&self, origin: &str, source: &str, source_id: usize) -> String {
+        let mut rendered = match self.span() {
+            Some(span) if span.source == source_id => {
+                diagnostic::render(origin, source, span, &self.to_string())
+            }
+            _ => format!("error: {}", self),
+        };
+        if let InterpreterError::Eval(err) = self {
+            _render_backtrace(&mut rendered, err.backtrace());
+        }
+        rendered
+    }
+}
+
+/// Appends one line per call, innermost first, collapsing repeated calls
+/// from the same place as deep recursion produces.
+fn _render_backtrace(rendered: &mut String, backtrace: &[TraceFrame]) {
+    if backtrace.is_empty() {
+        return;
+    }
+
+    rendered.push_str("\nbacktrace:");
+    let mut index = 0;
+    while index < backtrace.len() {
+        let frame = &backtrace[index];
+        let count = backtrace[index..]
+            .iter()
+            .take_while(|other| other.name == frame.name && other.span == frame.span)
+            .count();
+        let _ = write!(
+            rendered,
+            "\n  in {} called at {}:{}",
+            frame.name, frame.span.line, frame.span.column
+        );
+        if count > 1 {
+            let _ = write!(rendered, " ({} times)", count);
+        }
+        index += count;
+    }
+}
+
+impl fmt::Display for InterpreterError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpreterError::Lex(err) => err.fmt(f),
+            InterpreterError::Parse(err) => err.fmt(f),
+            InterpreterError::Eval(err) => err.fmt(f),
+        }
+    }
+}
+
+/// Owns everything needed to run Lisp source: the parser state, which
+/// remembers defined macros, and the evaluation context holding globals.
+/// Definitions persist between calls to `eval`.
+pub struct Interpreter {
+    parse_context: ParseContext,
+    context: EvalContext,
+    /// Every source evaluated so far, indexed by the `source` id of the
+    /// spans lexed from it.
+    sources: Vec<String>,
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Self {
+            parse_context: ParseContext::new(),
+            context: EvalContext::new(EvalFrame::empty()),
+            sources: Vec::new(),
+        }
+    }
+
+    /// Evaluates every form in `source` and returns the value of the last
+    /// one, or `nil` when there are none.
+    pub fn eval(&mut self, source: &str) -> Result<Value, InterpreterError> {
+        let source_id = self.sources.len();
+        self.sources.push(source.to_string());
+        let tokens = lex::lex_source(source, source_id).map_err(InterpreterError::Lex)?;
+        let mut result = Value::Literal(Literal::Nil);
+        for expr in parse::parse(&tokens, &mut self.parse_context) {
+            let expr = expr.map_err(InterpreterError::Parse)?;
+            result = eval(&expr, &mut self.context).map_err(InterpreterError::Eval)?;
+        }
+
+        Ok(result)
+    }
+
+    /// Renders `err` against the source its span points into. That is an
+    /// earlier input when the error comes from the body of a function
+    /// defined there. `origin` names where the sources came from.
+    pub fn render(&self, err: &InterpreterError, origin: &str) -> String {
+        let source_id = err.span().map_or(0, |span| span.source);
+        let source = self.sources.get(source_id).map_or("", String::as_str);
+        err.render(origin, source, source_id)
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.context.lookup_global(name)
+    }
+
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.context.set_global(name, value);
+    }
+
+    pub fn register_function<Args, F: IntoNativeFunction<Args>>(&mut self, name: &str, function: F) {
+        self.context.register_function(name, function);
+    }
+
+    pub fn context(&mut self) -> &mut EvalContext {
+        &mut self.context
+    }
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,13 @@
+pub mod base;
+pub mod convert;
+pub mod expand;
+pub mod frame;
+pub mod function;
+pub mod interpreter;
+pub mod number;
+pub mod operator;
+pub mod value;
+
+pub use convert::{FromValue, IntoValue};
+pub use interpreter::{Interpreter, InterpreterError};
+pub use value::Value;
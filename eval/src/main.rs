@@ -1,60 +1,131 @@
 use std::env;
 use std::fs;
 use std::io;
-use std::io::Write;
-
-use compiler::{lex, parse};
-
-mod base;
-mod frame;
-mod function;
-mod operator;
-mod value;
-
-fn _run_source(source: &str, context: &mut frame::EvalContext, verbose: bool) {
-    let tokens = lex::lex(source).expect("Couldnt Lex!");
-    for expr in parse::parse(&tokens) {
-        let res = base::eval(&expr.expect("Could Not Parse!"), context).expect("Couldnt Eval!");
-        if verbose {
-            println!("{}", res);
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+
+use compiler::lex::{lex, LexError};
+use eval::frame::{EVAL_STACK_MAX_DEPTH, EVAL_STACK_SIZE};
+use eval::Interpreter;
+use lisp::TokenKind;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+const HISTORY_FILE: &str = ".lisp_history";
+
+/// Returns true once every paren opened in `source` has been closed.
+/// Parens inside string literals don't count, and an unterminated string
+/// keeps the form open. Input that fails to lex otherwise counts as
+/// balanced, so that its error is shown.
+fn _is_balanced(source: &str) -> bool {
+    match lex(source) {
+        Ok(tokens) => {
+            let depth = tokens.iter().fold(0, |depth, token| match token.kind {
+                TokenKind::OpenParen => depth + 1,
+                TokenKind::CloseParen => depth - 1,
+                _ => depth,
+            });
+            depth <= 0
         }
+        Err(LexError::StringNeverClosed(_)) => false,
+        Err(_) => true,
     }
 }
 
-fn _prompt() -> std::io::Result<String> {
-    let mut line = String::new();
-    let stdin = io::stdin();
-    print!(">> ");
-    io::stdout().flush()?;
-    stdin.read_line(&mut line)?;
-    Ok(line)
+fn _history_path() -> Option<PathBuf> {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE))
 }
 
-fn repl() -> std::io::Result<()> {
-    println!("==== Welcome To Lisp! ====");
-    let frame = frame::EvalFrame::empty();
-    let mut context = frame::EvalContext::new(frame);
+/// Reads lines until the parens balance, showing a continuation prompt for
+/// every line after the first. Returns `None` at end of input.
+fn _prompt(editor: &mut DefaultEditor) -> rustyline::Result<Option<String>> {
+    let mut source = String::new();
 
     loop {
-        let line = _prompt()?;
-        _run_source(&line, &mut context, true);
+        let prompt = if source.is_empty() { ">> " } else { ".. " };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                source.push_str(&line);
+                source.push('\n');
+                if source.trim().is_empty() {
+                    source.clear();
+                } else if _is_balanced(&source) {
+                    editor.add_history_entry(source.trim_end())?;
+                    return Ok(Some(source));
+                }
+            }
+            // Ctrl-C drops the form being typed rather than leaving the REPL.
+            Err(ReadlineError::Interrupted) => source.clear(),
+            Err(ReadlineError::Eof) => return Ok(None),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// An interpreter allowed the deeper nesting the runner thread's stack
+/// has room for.
+fn _interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.context().set_max_depth(EVAL_STACK_MAX_DEPTH);
+    interpreter
+}
+
+fn repl() -> rustyline::Result<()> {
+    println!("==== Welcome To Lisp! ====");
+    let mut interpreter = _interpreter();
+
+    let mut editor = DefaultEditor::new()?;
+    let history_path = _history_path();
+    if let Some(path) = &history_path {
+        // There is no history yet the first time the REPL runs.
+        let _ = editor.load_history(path);
+    }
+
+    while let Some(source) = _prompt(&mut editor)? {
+        match interpreter.eval(&source) {
+            Ok(value) => println!("{}", value),
+            Err(err) => eprintln!("{}", interpreter.render(&err, "<repl>")),
+        }
+    }
+
+    if let Some(path) = &history_path {
+        editor.save_history(path)?;
     }
+
+    Ok(())
 }
 
 fn execute_file(path: &str) -> std::io::Result<()> {
     let source = fs::read_to_string(path)?;
-    let frame = frame::EvalFrame::empty();
-    let mut context = frame::EvalContext::new(frame);
-    _run_source(&source, &mut context, false);
+    let mut interpreter = _interpreter();
+
+    if let Err(err) = interpreter.eval(&source) {
+        eprintln!("{}", interpreter.render(&err, path));
+        process::exit(1);
+    }
 
     Ok(())
 }
 
-fn main() -> std::io::Result<()> {
+fn run() -> std::io::Result<()> {
     let args: Vec<String> = env::args().collect();
 
     match &args[..] {
         [.., _, path] => execute_file(path),
-        _ => repl(),
+        _ => repl().map_err(io::Error::other),
+    }
+}
+
+fn main() -> std::io::Result<()> {
+    // Run on a bigger stack than the main thread's, to allow deeper nesting
+    // than the default depth limit.
+    let runner = thread::Builder::new()
+        .stack_size(EVAL_STACK_SIZE)
+        .spawn(run)?;
+    match runner.join() {
+        Ok(result) => result,
+        // The panic message has already been printed.
+        Err(_) => process::exit(101),
     }
 }
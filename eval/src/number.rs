@@ -0,0 +1,260 @@
+use std::cmp::Ordering;
+use std::ops::{Add, Mul, Sub};
+
+use lisp::{BigInt, BigRational, Literal};
+use num_integer::Integer;
+use num_traits::{FromPrimitive, Pow, Signed, ToPrimitive, Zero};
+
+use crate::{
+    base::EvalError,
+    convert::{FromValue, IntoValue},
+    value::Value,
+};
+
+/// A Lisp number. Integers and ratios are exact: integers that overflow an
+/// `i64` become `BigInteger`s, and dividing integers gives a `Ratio` unless
+/// the quotient is whole. A float on either side makes the result a float.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Number {
+    Integer(i64),
+    BigInteger(BigInt),
+    Ratio(BigRational),
+    Float(f64),
+}
+
+/// The most bits an exact result of `expt` may have, about 315,000 decimal
+/// digits, so that huge exponents fail instead of exhausting memory.
+pub const MAX_EXPT_BITS: u64 = 1 << 20;
+
+/// How `truncate`, `floor` and `round` turn a quotient into an integer.
+#[derive(Clone, Copy, Debug)]
+pub enum Rounding {
+    Truncate,
+    Floor,
+    /// To the nearest integer, with halves going to the even one.
+    Nearest,
+}
+
+impl Number {
+    pub fn to_f64(&self) -> f64 {
+        match self {
+            Number::Integer(integer) => *integer as f64,
+            Number::BigInteger(integer) => integer.to_f64().unwrap_or(f64::NAN),
+            Number::Ratio(ratio) => ratio.to_f64().unwrap_or(f64::NAN),
+            Number::Float(float) => *float,
+        }
+    }
+
+    /// Returns the number as a ratio, or `None` for floats.
+    pub fn to_ratio(&self) -> Option<BigRational> {
+        match self {
+            Number::Integer(integer) => Some(BigRational::from_integer(BigInt::from(*integer))),
+            Number::BigInteger(integer) => Some(BigRational::from_integer(integer.clone())),
+            Number::Ratio(ratio) => Some(ratio.clone()),
+            Number::Float(_) => None,
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        match self {
+            Number::Integer(integer) => *integer == 0,
+            Number::BigInteger(integer) => integer.is_zero(),
+            Number::Ratio(ratio) => ratio.is_zero(),
+            Number::Float(float) => *float == 0.0,
+        }
+    }
+
+    /// Divides, giving a ratio when integers don't divide evenly.
+    pub fn checked_div(self, other: Number) -> Result<Number, EvalError> {
+        if other.is_zero() {
+            return Err(EvalError::DivisionByZero);
+        }
+
+        match (self.to_ratio(), other.to_ratio()) {
+            (Some(left), Some(right)) => Ok(_exact(Literal::ratio(left / right))),
+            _ => Ok(Number::Float(self.to_f64() / other.to_f64())),
+        }
+    }
+
+    /// Divides by `divisor` and rounds the quotient to an integer.
+    pub fn quotient(self, divisor: Number, rounding: Rounding) -> Result<Number, EvalError> {
+        if divisor.is_zero() {
+            return Err(EvalError::DivisionByZero);
+        }
+
+        match (self.to_ratio(), divisor.to_ratio()) {
+            (Some(left), Some(right)) => Ok(_exact(Literal::integer(_round(left / right, rounding)))),
+            _ => {
+                let quotient = self.to_f64() / divisor.to_f64();
+                let rounded = match rounding {
+                    Rounding::Truncate => quotient.trunc(),
+                    Rounding::Floor => quotient.floor(),
+                    Rounding::Nearest => quotient.round_ties_even(),
+                };
+                match BigInt::from_f64(rounded) {
+                    Some(integer) => Ok(_exact(Literal::integer(integer))),
+                    None => Err(EvalError::TypeMismatch("finite number", quotient.into_value())),
+                }
+            }
+        }
+    }
+
+    /// What is left after dividing by `divisor` with the quotient rounded by
+    /// `rounding`: `Floor` gives `mod`, `Truncate` gives `rem`.
+    pub fn remainder(self, divisor: Number, rounding: Rounding) -> Result<Number, EvalError> {
+        let quotient = self.clone().quotient(divisor.clone(), rounding)?;
+        Ok(self - divisor * quotient)
+    }
+
+    /// Raises to `power`, exactly when the base is exact and the power an
+    /// integer. Exact results longer than `MAX_EXPT_BITS` are an error.
+    pub fn pow(self, power: Number) -> Result<Number, EvalError> {
+        if self.is_zero() && power.to_f64() < 0.0 {
+            return Err(EvalError::DivisionByZero);
+        }
+
+        let exponent = match power {
+            Number::Integer(exponent) => Some(exponent),
+            _ => None,
+        };
+        match (self.to_ratio(), exponent) {
+            (Some(base), Some(exponent)) => Ok(_exact(Literal::ratio(_exact_pow(base, exponent)?))),
+            _ if self.to_f64() < 0.0 && power.to_f64().fract() != 0.0 => {
+                Err(EvalError::TypeMismatch("non-negative number", self.into_value()))
+            }
+            _ => Ok(Number::Float(self.to_f64().powf(power.to_f64()))),
+        }
+    }
+
+    pub fn abs(self) -> Number {
+        match self {
+            Number::Integer(integer) => match integer.checked_abs() {
+                Some(integer) => Number::Integer(integer),
+                None => Number::BigInteger(BigInt::from(integer).abs()),
+            },
+            Number::BigInteger(integer) => Number::BigInteger(integer.abs()),
+            Number::Ratio(ratio) => Number::Ratio(ratio.abs()),
+            Number::Float(float) => Number::Float(float.abs()),
+        }
+    }
+
+    /// Compares by value, so `1` and `1.0` are equal.
+    pub fn compare(&self, other: &Number) -> Option<Ordering> {
+        match (self, other) {
+            (Number::Integer(left), Number::Integer(right)) => Some(left.cmp(right)),
+            _ => match (self.to_ratio(), other.to_ratio()) {
+                (Some(left), Some(right)) => Some(left.cmp(&right)),
+                _ => self.to_f64().partial_cmp(&other.to_f64()),
+            },
+        }
+    }
+}
+
+/// The number made by `Literal::integer` or `Literal::ratio`.
+fn _exact(literal: Literal) -> Number {
+    match literal {
+        Literal::Integer(integer) => Number::Integer(integer),
+        Literal::BigInteger(integer) => Number::BigInteger(integer),
+        Literal::Ratio(ratio) => Number::Ratio(*ratio),
+        literal => unreachable!("{} is not an exact number", literal),
+    }
+}
+
+fn _exact_pow(base: BigRational, exponent: i64) -> Result<BigRational, EvalError> {
+    let bits = base.numer().bits().max(base.denom().bits());
+    if bits <= 1 {
+        // 0, 1 and -1 only depend on the sign and parity of the exponent.
+        let exponent = exponent.signum() * (2 - exponent.rem_euclid(2));
+        return Ok(base.pow(exponent as i32));
+    }
+
+    // A base of `bits` bits is at least 2^(bits - 1).
+    if (bits - 1).saturating_mul(exponent.unsigned_abs()) > MAX_EXPT_BITS {
+        return Err(EvalError::NumberTooLarge);
+    }
+    Ok(base.pow(exponent as i32))
+}
+
+fn _combine(
+    left: Number,
+    right: Number,
+    integer: fn(i64, i64) -> Option<i64>,
+    exact: fn(BigRational, BigRational) -> BigRational,
+    float: fn(f64, f64) -> f64,
+) -> Number {
+    // Small integers take the fast path until they overflow.
+    if let (Number::Integer(left), Number::Integer(right)) = (&left, &right) {
+        if let Some(result) = integer(*left, *right) {
+            return Number::Integer(result);
+        }
+    }
+
+    match (left.to_ratio(), right.to_ratio()) {
+        (Some(left), Some(right)) => _exact(Literal::ratio(exact(left, right))),
+        _ => Number::Float(float(left.to_f64(), right.to_f64())),
+    }
+}
+
+fn _round(ratio: BigRational, rounding: Rounding) -> BigInt {
+    match rounding {
+        Rounding::Truncate => ratio.trunc().to_integer(),
+        Rounding::Floor => ratio.floor().to_integer(),
+        Rounding::Nearest => {
+            let floor = ratio.floor();
+            let half = BigRational::new(BigInt::from(1), BigInt::from(2));
+            let floor_is_even = floor.to_integer().is_even();
+            match (ratio - &floor).cmp(&half) {
+                Ordering::Less => floor.to_integer(),
+                Ordering::Equal if floor_is_even => floor.to_integer(),
+                _ => floor.to_integer() + 1,
+            }
+        }
+    }
+}
+
+impl Add for Number {
+    type Output = Number;
+
+    fn add(self, other: Number) -> Number {
+        _combine(self, other, i64::checked_add, |left, right| left + right, |left, right| left + right)
+    }
+}
+
+impl Sub for Number {
+    type Output = Number;
+
+    fn sub(self, other: Number) -> Number {
+        _combine(self, other, i64::checked_sub, |left, right| left - right, |left, right| left - right)
+    }
+}
+
+impl Mul for Number {
+    type Output = Number;
+
+    fn mul(self, other: Number) -> Number {
+        _combine(self, other, i64::checked_mul, |left, right| left * right, |left, right| left * right)
+    }
+}
+
+impl FromValue for Number {
+    fn from_value(value: Value) -> Result<Self, EvalError> {
+        match value {
+            Value::Literal(Literal::Integer(integer)) => Ok(Number::Integer(integer)),
+            Value::Literal(Literal::BigInteger(integer)) => Ok(Number::BigInteger(integer)),
+            Value::Literal(Literal::Ratio(ratio)) => Ok(Number::Ratio(*ratio)),
+            Value::Literal(Literal::Float(float)) => Ok(Number::Float(float)),
+            value => Err(EvalError::TypeMismatch("number", value)),
+        }
+    }
+}
+
+impl IntoValue for Number {
+    fn into_value(self) -> Value {
+        match self {
+            Number::Integer(integer) => Value::Literal(Literal::Integer(integer)),
+            Number::BigInteger(integer) => Value::Literal(Literal::BigInteger(integer)),
+            Number::Ratio(ratio) => Value::Literal(Literal::Ratio(Box::new(ratio))),
+            Number::Float(float) => Value::Literal(Literal::Float(float)),
+        }
+    }
+}
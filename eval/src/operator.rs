@@ -1,86 +1,626 @@
-use lisp::{Expression, Literal, Operator};
+use lisp::{Binding, CaseClause, CondClause, Datum, ErrorHandler, Expression, ExpressionKind, Literal, LoopClause, LocalFunction, Operator, Span};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::rc::Rc;
 
 use crate::{
-    base::{eval, EvalError, EvalResult},
-    frame::EvalContext,
-    function::custom::CustomFunction,
+    base::{eval, EvalError, EvalResult, Signal, TailStep},
+    convert::{FromValue, IntoValue},
+    expand::{call_macro, to_expression},
+    frame::{EvalContext, EvalFrame, Scope},
+    function::custom::{bind_labels, CustomFunction},
+    number::Number,
     value::Value,
 };
 
-fn eval_setq(name: String, expr: &Expression, context: &mut EvalContext) -> EvalResult {
+fn eval_setq(name: &str, expr: &Expression, context: &mut EvalContext) -> EvalResult {
     let value = eval(expr, context)?;
-    context
-        .head_mut()
-        .locals
-        .insert(name.clone(), value.clone());
+    context.set_local(name, value.clone());
     Ok(value)
 }
 
-fn eval_progn(expressions: &[Expression], context: &mut EvalContext) -> EvalResult {
-    let mut result = Err(EvalError::UndefinedBehaviour);
+/// Evaluates all but the last expression, which is left for `eval` to
+/// continue with in tail position.
+fn eval_progn(expressions: &[Expression], context: &mut EvalContext) -> Result<TailStep, EvalError> {
+    let (last, expressions) = expressions.split_last().ok_or(EvalError::UndefinedBehaviour)?;
     for expr in expressions {
-        match eval(&expr, context) {
-            Ok(value) => {
-                result = Ok(value);
-            }
-            error @ _ => {
-                return error;
-            }
-        }
+        eval(expr, context)?;
     }
-    result
+    Ok(TailStep::Eval(last.clone()))
 }
 
 fn eval_if(
     condition: &Expression,
     positive_case: &Expression,
-    negative_case_or_none: Option<Box<Expression>>,
+    negative_case_or_none: Option<&Expression>,
     context: &mut EvalContext,
-) -> EvalResult {
-    if let Value::Literal(Literal::True) = eval(condition, context)? {
-        return eval(positive_case, context);
+) -> Result<TailStep, EvalError> {
+    if eval(condition, context)?.is_true() {
+        return Ok(TailStep::Eval(positive_case.clone()));
     }
 
-    if let Some(negative_case) = negative_case_or_none {
-        return eval(&*negative_case, context);
+    match negative_case_or_none {
+        Some(negative_case) => Ok(TailStep::Eval(negative_case.clone())),
+        None => Ok(TailStep::Value(Value::Literal(Literal::Nil))),
+    }
+}
+
+/// Returns the first `nil` value, or the last value when all are true.
+fn eval_and(expressions: &[Expression], context: &mut EvalContext) -> EvalResult {
+    let mut result = Value::Literal(Literal::True);
+    for expr in expressions {
+        result = eval(expr, context)?;
+        if !result.is_true() {
+            break;
+        }
     }
+    Ok(result)
+}
 
+/// Returns the first true value, or `nil` when there is none.
+fn eval_or(expressions: &[Expression], context: &mut EvalContext) -> EvalResult {
+    for expr in expressions {
+        let value = eval(expr, context)?;
+        if value.is_true() {
+            return Ok(value);
+        }
+    }
     Ok(Value::Literal(Literal::Nil))
 }
 
+/// Continues with `body` in tail position, or returns `nil` when it is empty.
+fn _eval_body(body: &[Expression], context: &mut EvalContext) -> Result<TailStep, EvalError> {
+    match body.is_empty() {
+        true => Ok(TailStep::Value(Value::Literal(Literal::Nil))),
+        false => eval_progn(body, context),
+    }
+}
+
+fn eval_cond(clauses: &[CondClause], context: &mut EvalContext) -> Result<TailStep, EvalError> {
+    for (test, body) in clauses {
+        let value = eval(test, context)?;
+        if !value.is_true() {
+            continue;
+        }
+        return match body.is_empty() {
+            true => Ok(TailStep::Value(value)),
+            false => eval_progn(body, context),
+        };
+    }
+    Ok(TailStep::Value(Value::Literal(Literal::Nil)))
+}
+
+/// Runs the body of the first clause with a key equal to the value of
+/// `key`. With `exhaustive` set, as for `ecase`, no match is an error.
+fn eval_case(
+    key: &Expression,
+    clauses: &[CaseClause],
+    exhaustive: bool,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    let value = eval(key, context)?;
+    for (keys, body) in clauses {
+        let matches = match keys {
+            None => true,
+            Some(keys) => keys
+                .iter()
+                .map(|key| eval_datum(key, context))
+                .collect::<Result<Vec<Value>, EvalError>>()?
+                .contains(&value),
+        };
+        if matches {
+            return _eval_body(body, context);
+        }
+    }
+
+    match exhaustive {
+        true => Err(EvalError::UnmatchedCase(value)),
+        false => Ok(TailStep::Value(Value::Literal(Literal::Nil))),
+    }
+}
+
+fn eval_when(
+    condition: &Expression,
+    body: &[Expression],
+    expected: bool,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    match eval(condition, context)?.is_true() == expected {
+        true => _eval_body(body, context),
+        false => Ok(TailStep::Value(Value::Literal(Literal::Nil))),
+    }
+}
+
 fn eval_function(
-    name: String,
-    parameters: Vec<String>,
-    code: Expression,
+    name: &str,
+    parameters: &[String],
+    code: &Expression,
     context: &mut EvalContext,
 ) -> EvalResult {
-    let function = CustomFunction::new(parameters, code);
+    let function = CustomFunction::new(name, parameters.to_vec(), code.clone(), context.current_scope());
     let value = Value::Symbol(Rc::new(function));
-    context.add_function(&name, &value);
+    context.add_function(name, &value);
     Ok(value)
 }
 
-fn eval_lambda(
-    parameters: Vec<String>,
-    code: Expression,
-    _context: &mut EvalContext,
-) -> EvalResult {
-    Ok(Value::Symbol(Rc::new(CustomFunction::new(
-        parameters, code,
+fn eval_lambda(parameters: &[String], code: &Expression, context: &mut EvalContext) -> EvalResult {
+    Ok(Value::Symbol(Rc::new(CustomFunction::lambda(
+        parameters.to_vec(),
+        code.clone(),
+        context.current_scope(),
     ))))
 }
 
-pub fn eval_operator(operator: Operator, context: &mut EvalContext) -> EvalResult {
-    match operator {
-        Operator::SetQ(name, expr) => eval_setq(name, &expr, context),
-        Operator::ProgN(expressions) => eval_progn(&expressions, context),
+fn eval_datum(datum: &Datum, context: &mut EvalContext) -> EvalResult {
+    match datum {
+        Datum::Literal(literal) => Ok(Value::Literal(literal.clone())),
+        Datum::Name(name) => Ok(Value::Name(name.clone())),
+        Datum::Unquote(expr) => eval(expr, context),
+        Datum::UnquoteSplicing(_) => Err(EvalError::BadArguments),
+        Datum::List(items) => {
+            let mut values = vec![];
+            for item in items {
+                if let Datum::UnquoteSplicing(expr) = item {
+                    let spliced = eval(expr, context)?;
+                    values.extend(spliced.to_vec().ok_or(EvalError::BadArguments)?);
+                } else {
+                    values.push(eval_datum(item, context)?);
+                }
+            }
+            Ok(Value::list(values))
+        }
+    }
+}
+
+fn eval_macro(
+    name: &str,
+    parameters: &[String],
+    code: &Expression,
+    context: &mut EvalContext,
+) -> EvalResult {
+    let function = CustomFunction::new(name, parameters.to_vec(), code.clone(), context.current_scope());
+    context.add_macro(name, &Value::Symbol(Rc::new(function)));
+    Ok(Value::Name(name.to_string()))
+}
+
+fn eval_macro_call(
+    name: &str,
+    arguments: &[Datum],
+    span: Span,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    let function = match context.lookup_macro(name) {
+        Some(Value::Symbol(function)) => function,
+        _ => return Err(EvalError::NameNotFound(name.to_string())),
+    };
+    let arguments = arguments
+        .iter()
+        .map(|argument| eval_datum(argument, context))
+        .collect::<Result<Vec<Value>, EvalError>>()?;
+
+    let expansion = call_macro(&*function, arguments, context)?;
+    Ok(TailStep::Eval(to_expression(&expansion, span, context)?))
+}
+
+/// Leaves `body` for `eval` to continue with in `scope`, as the last
+/// step of the binding forms.
+fn enter_body(scope: Scope, body: &[Expression], span: Span) -> Result<TailStep, EvalError> {
+    let expr = match body {
+        [] => Expression::new(ExpressionKind::Literal(Literal::Nil), span),
+        [expr] => expr.clone(),
+        _ => Expression::new(ExpressionKind::Operator(Box::new(Operator::ProgN(body.to_vec()))), span),
+    };
+    Ok(TailStep::Enter(scope, expr))
+}
+
+fn eval_let(
+    bindings: &[Binding],
+    body: &[Expression],
+    span: Span,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    let mut locals = HashMap::new();
+    for (name, expr) in bindings {
+        locals.insert(name.clone(), eval(expr, context)?);
+    }
+
+    let frame = EvalFrame::with_parent(locals, context.current_scope());
+    enter_body(Rc::new(RefCell::new(frame)), body, span)
+}
+
+fn eval_let_star(
+    bindings: &[Binding],
+    body: &[Expression],
+    span: Span,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    // Every value is evaluated inside the new frame, so it sees the
+    // bindings made before it.
+    let frame = EvalFrame::with_parent(HashMap::new(), context.current_scope());
+    let scope = Rc::new(RefCell::new(frame));
+    let previous = context.enter_scope(scope.clone());
+    let result = bindings.iter().try_for_each(|(name, expr)| {
+        let value = eval(expr, context)?;
+        scope.borrow_mut().locals.insert(name.clone(), value);
+        Ok(())
+    });
+    context.exit_scope(previous);
+    result?;
+
+    enter_body(scope, body, span)
+}
+
+/// Binds local functions in a new frame. With `is_recursive` (`labels`)
+/// the functions close over that frame and can call each other, while
+/// `flet` functions only see the enclosing scope.
+fn eval_local_functions(
+    functions: &[LocalFunction],
+    body: &[Expression],
+    is_recursive: bool,
+    span: Span,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    let outer = context.current_scope();
+    if is_recursive {
+        return enter_body(bind_labels(functions.into(), outer), body, span);
+    }
+
+    let scope = Rc::new(RefCell::new(EvalFrame::with_parent(HashMap::new(), outer.clone())));
+    for (name, parameters, code) in functions {
+        let function = CustomFunction::new(name, parameters.clone(), code.clone(), outer.clone());
+        scope
+            .borrow_mut()
+            .functions
+            .insert(name.clone(), Value::Symbol(Rc::new(function)));
+    }
+
+    enter_body(scope, body, span)
+}
+
+/// Non-local exits are never handled, as they aren't errors.
+fn _handles(condition_type: &str, error: &EvalError) -> bool {
+    !error.is_signal()
+        && (matches!(condition_type, "t" | "condition" | "error")
+            || condition_type == error.condition_type())
+}
+
+/// Evaluates `expr`, and on failure the body of the first clause whose type
+/// matches the error, with the clause variable bound to the condition.
+fn eval_handler_case(
+    expr: &Expression,
+    handlers: &[ErrorHandler],
+    span: Span,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    let error = match eval(expr, context) {
+        Ok(value) => return Ok(TailStep::Value(value)),
+        Err(error) => error,
+    };
+
+    match handlers
+        .iter()
+        .find(|(condition_type, _, _)| _handles(condition_type, &error))
+    {
+        Some((_, variable, body)) => {
+            let mut locals = HashMap::new();
+            if let Some(variable) = variable {
+                locals.insert(variable.clone(), error.condition_value());
+            }
+            let frame = EvalFrame::with_parent(locals, context.current_scope());
+            enter_body(Rc::new(RefCell::new(frame)), body, span)
+        }
+        None => Err(error),
+    }
+}
+
+fn eval_ignore_errors(expressions: &[Expression], context: &mut EvalContext) -> EvalResult {
+    let mut result = Value::Literal(Literal::Nil);
+    for expr in expressions {
+        match eval(expr, context) {
+            Ok(value) => result = value,
+            Err(error) if error.is_signal() => return Err(error),
+            Err(_) => return Ok(Value::Literal(Literal::Nil)),
+        }
+    }
+    Ok(result)
+}
+
+/// Runs the cleanup forms however `protected` finishes. An error from the
+/// cleanup replaces the protected form's result.
+fn eval_unwind_protect(
+    protected: &Expression,
+    cleanup: &[Expression],
+    context: &mut EvalContext,
+) -> EvalResult {
+    let result = eval(protected, context);
+    for expr in cleanup {
+        eval(expr, context)?;
+    }
+    result
+}
+
+fn eval_sequence(expressions: &[Expression], context: &mut EvalContext) -> EvalResult {
+    let mut result = Value::Literal(Literal::Nil);
+    for expr in expressions {
+        result = eval(expr, context)?;
+    }
+    Ok(result)
+}
+
+fn eval_block(name: &str, body: &[Expression], context: &mut EvalContext) -> EvalResult {
+    let block = context.new_block_id();
+    let mut frame = EvalFrame::with_parent(HashMap::new(), context.current_scope());
+    frame.blocks.insert(name.to_string(), block);
+
+    let previous = context.enter_scope(Rc::new(RefCell::new(frame)));
+    let result = eval_sequence(body, context);
+    context.exit_scope(previous);
+    result.or_else(|error| error.returned_from(Some(block)))
+}
+
+fn eval_return_from(
+    name: &str,
+    value: Option<&Expression>,
+    context: &mut EvalContext,
+) -> EvalResult {
+    let block = context
+        .lookup_block(name)
+        .ok_or_else(|| EvalError::UnknownBlock(name.to_string()))?;
+    let value = match value {
+        Some(expr) => eval(expr, context)?,
+        None => Value::Literal(Literal::Nil),
+    };
+    Err(EvalError::Signal(Signal::ReturnFrom(block, name.to_string(), value)))
+}
+
+fn eval_catch(tag: &Expression, body: &[Expression], context: &mut EvalContext) -> EvalResult {
+    let tag = eval(tag, context)?;
+    context.enter_catch(tag.clone());
+    let result = eval_sequence(body, context);
+    context.exit_catch();
+
+    match result {
+        Err(EvalError::Signal(Signal::Throw(thrown, value))) if thrown == tag => Ok(value),
+        result => result,
+    }
+}
+
+fn eval_throw(tag: &Expression, value: &Expression, context: &mut EvalContext) -> EvalResult {
+    let tag = eval(tag, context)?;
+    let value = eval(value, context)?;
+    if !context.has_catch(&tag) {
+        return Err(EvalError::UnknownCatchTag(tag));
+    }
+    Err(EvalError::Signal(Signal::Throw(tag, value)))
+}
+
+/// Runs `run` in a new frame holding an implicit `block nil`, as the
+/// iteration forms do, so `(return-from nil)` leaves them early.
+fn _in_nil_block(
+    context: &mut EvalContext,
+    run: impl FnOnce(&Scope, &mut EvalContext) -> EvalResult,
+) -> EvalResult {
+    let block = context.new_block_id();
+    let mut frame = EvalFrame::with_parent(HashMap::new(), context.current_scope());
+    frame.blocks.insert(String::from("nil"), block);
+
+    let scope = Rc::new(RefCell::new(frame));
+    let previous = context.enter_scope(scope.clone());
+    let result = run(&scope, context);
+    context.exit_scope(previous);
+    result.or_else(|error| error.returned_from(Some(block)))
+}
+
+fn eval_while(condition: &Expression, body: &[Expression], context: &mut EvalContext) -> EvalResult {
+    _in_nil_block(context, |_, context| {
+        while eval(condition, context)?.is_true() {
+            eval_sequence(body, context)?;
+        }
+        Ok(Value::Literal(Literal::Nil))
+    })
+}
+
+fn _run_iteration(
+    name: &str,
+    values: impl Iterator<Item = Value>,
+    last: Value,
+    result: Option<&Expression>,
+    body: &[Expression],
+    scope: &Scope,
+    context: &mut EvalContext,
+) -> EvalResult {
+    for value in values {
+        scope.borrow_mut().locals.insert(name.to_string(), value);
+        eval_sequence(body, context)?;
+    }
+
+    scope.borrow_mut().locals.insert(name.to_string(), last);
+    match result {
+        Some(result) => eval(result, context),
+        None => Ok(Value::Literal(Literal::Nil)),
+    }
+}
+
+/// Evaluates `body` once per value with `name` bound to it in a new frame,
+/// then `result` with `name` bound to `last`.
+fn eval_iteration(
+    name: &str,
+    values: impl Iterator<Item = Value>,
+    last: Value,
+    result: Option<&Expression>,
+    body: &[Expression],
+    context: &mut EvalContext,
+) -> EvalResult {
+    _in_nil_block(context, |scope, context| {
+        _run_iteration(name, values, last, result, body, scope, context)
+    })
+}
+
+fn eval_dotimes(
+    name: &str,
+    count: &Expression,
+    result: Option<&Expression>,
+    body: &[Expression],
+    context: &mut EvalContext,
+) -> EvalResult {
+    let count = i64::from_value(eval(count, context)?)?.max(0);
+    let values = (0..count).map(i64::into_value);
+    let last = count.into_value();
+    eval_iteration(name, values, last, result, body, context)
+}
+
+fn eval_dolist(
+    name: &str,
+    list: &Expression,
+    result: Option<&Expression>,
+    body: &[Expression],
+    context: &mut EvalContext,
+) -> EvalResult {
+    let values = Vec::<Value>::from_value(eval(list, context)?)?;
+    let last = Value::Literal(Literal::Nil);
+    eval_iteration(name, values.into_iter(), last, result, body, context)
+}
+
+/// Where a `for` clause of a `loop` is in its iteration.
+enum LoopState {
+    Range(Number, Option<Number>),
+    List(std::vec::IntoIter<Value>),
+    None,
+}
+
+fn _loop_states(clauses: &[LoopClause], context: &mut EvalContext) -> Result<Vec<LoopState>, EvalError> {
+    clauses
+        .iter()
+        .map(|clause| match clause {
+            LoopClause::ForFrom(_, start, end) => {
+                let start = Number::from_value(eval(start, context)?)?;
+                let end = match end {
+                    Some(end) => Some(Number::from_value(eval(end, context)?)?),
+                    None => None,
+                };
+                Ok(LoopState::Range(start, end))
+            }
+            LoopClause::ForIn(_, list) => {
+                let values = Vec::<Value>::from_value(eval(list, context)?)?;
+                Ok(LoopState::List(values.into_iter()))
+            }
+            _ => Ok(LoopState::None),
+        })
+        .collect()
+}
+
+/// Runs the clauses in order until a `for` runs out or a `while` fails.
+/// Returns the collected list, the sum, or `nil` without either.
+fn _run_loop(clauses: &[LoopClause], scope: &Scope, context: &mut EvalContext) -> EvalResult {
+    let mut states = _loop_states(clauses, context)?;
+    let mut collected = vec![];
+    let mut sum = Number::Integer(0);
+
+    'iterations: loop {
+        for (clause, state) in clauses.iter().zip(states.iter_mut()) {
+            match (clause, state) {
+                (LoopClause::ForFrom(name, ..), LoopState::Range(current, end)) => {
+                    if end.as_ref().is_some_and(|end| current.compare(end) == Some(Ordering::Greater)) {
+                        break 'iterations;
+                    }
+                    scope.borrow_mut().locals.insert(name.clone(), current.clone().into_value());
+                    *current = current.clone() + Number::Integer(1);
+                }
+                (LoopClause::ForIn(name, _), LoopState::List(values)) => match values.next() {
+                    Some(value) => {
+                        scope.borrow_mut().locals.insert(name.clone(), value);
+                    }
+                    None => break 'iterations,
+                },
+                (LoopClause::While(condition), _) => {
+                    if !eval(condition, context)?.is_true() {
+                        break 'iterations;
+                    }
+                }
+                (LoopClause::Do(body), _) => {
+                    eval_sequence(body, context)?;
+                }
+                (LoopClause::Collect(value), _) => collected.push(eval(value, context)?),
+                (LoopClause::Sum(value), _) => {
+                    sum = sum + Number::from_value(eval(value, context)?)?
+                }
+                _ => return Err(EvalError::UndefinedBehaviour),
+            }
+        }
+    }
+
+    let collects = clauses.iter().any(|clause| matches!(clause, LoopClause::Collect(_)));
+    let sums = clauses.iter().any(|clause| matches!(clause, LoopClause::Sum(_)));
+    match (collects, sums) {
+        (true, _) => Ok(Value::list(collected)),
+        (false, true) => Ok(sum.into_value()),
+        (false, false) => Ok(Value::Literal(Literal::Nil)),
+    }
+}
+
+fn eval_loop(clauses: &[LoopClause], context: &mut EvalContext) -> EvalResult {
+    _in_nil_block(context, |scope, context| _run_loop(clauses, scope, context))
+}
+
+/// Evaluates `operator`, handing expressions in tail position back to
+/// `eval` instead of evaluating them here.
+pub fn eval_operator(
+    operator: &Operator,
+    span: Span,
+    context: &mut EvalContext,
+) -> Result<TailStep, EvalError> {
+    let value = match operator {
+        Operator::ProgN(expressions) => return eval_progn(expressions, context),
         Operator::If(condition, positive_case, negative_case_or_none) => {
-            eval_if(&*condition, &*positive_case, negative_case_or_none, context)
+            return eval_if(condition, positive_case, negative_case_or_none.as_deref(), context)
         }
+        Operator::MacroCall(name, arguments) => {
+            return eval_macro_call(name, arguments, span, context)
+        }
+        Operator::Let(bindings, body) => return eval_let(bindings, body, span, context),
+        Operator::LetStar(bindings, body) => {
+            return eval_let_star(bindings, body, span, context)
+        }
+        Operator::FLet(functions, body) => {
+            return eval_local_functions(functions, body, false, span, context)
+        }
+        Operator::Labels(functions, body) => {
+            return eval_local_functions(functions, body, true, span, context)
+        }
+        Operator::Cond(clauses) => return eval_cond(clauses, context),
+        Operator::Case(key, clauses) => return eval_case(key, clauses, false, context),
+        Operator::ECase(key, clauses) => return eval_case(key, clauses, true, context),
+        Operator::When(condition, body) => return eval_when(condition, body, true, context),
+        Operator::Unless(condition, body) => return eval_when(condition, body, false, context),
+        Operator::HandlerCase(expr, handlers) => {
+            return eval_handler_case(expr, handlers, span, context)
+        }
+        Operator::SetQ(name, expr) => eval_setq(name, expr, context),
         Operator::Function(name, parameters, code) => {
-            eval_function(name, parameters, *code, context)
+            eval_function(name, parameters, code, context)
         }
-        Operator::Lambda(parameters, code) => eval_lambda(parameters, *code, context),
-    }
+        Operator::Lambda(parameters, code) => eval_lambda(parameters, code, context),
+        Operator::Quote(datum) | Operator::Quasiquote(datum) => eval_datum(datum, context),
+        Operator::Macro(name, parameters, code) => eval_macro(name, parameters, code, context),
+        Operator::And(expressions) => eval_and(expressions, context),
+        Operator::Or(expressions) => eval_or(expressions, context),
+        Operator::IgnoreErrors(expressions) => eval_ignore_errors(expressions, context),
+        Operator::UnwindProtect(protected, cleanup) => {
+            eval_unwind_protect(protected, cleanup, context)
+        }
+        Operator::Block(name, body) => eval_block(name, body, context),
+        Operator::ReturnFrom(name, value) => eval_return_from(name, value.as_deref(), context),
+        Operator::Catch(tag, body) => eval_catch(tag, body, context),
+        Operator::Throw(tag, value) => eval_throw(tag, value, context),
+        Operator::While(condition, body) => eval_while(condition, body, context),
+        Operator::DoTimes(name, count, result, body) => {
+            eval_dotimes(name, count, result.as_deref(), body, context)
+        }
+        Operator::DoList(name, list, result, body) => {
+            eval_dolist(name, list, result.as_deref(), body, context)
+        }
+        Operator::Loop(clauses) => eval_loop(clauses, context),
+    };
+    value.map(TailStep::Value)
 }
@@ -8,6 +8,90 @@ use lisp::Literal;
 pub enum Value {
     Literal(Literal),
     Symbol(Rc<dyn Function>),
+    Name(String),
+    Cons(Rc<Cons>),
+}
+
+#[derive(Clone)]
+pub struct Cons {
+    pub car: Value,
+    pub cdr: Value,
+}
+
+impl Value {
+    pub fn cons(car: Value, cdr: Value) -> Self {
+        Value::Cons(Rc::new(Cons { car, cdr }))
+    }
+
+    pub fn list(values: Vec<Value>) -> Self {
+        Self::list_with_tail(values, Value::Literal(Literal::Nil))
+    }
+
+    pub fn list_with_tail(values: Vec<Value>, tail: Value) -> Self {
+        values
+            .into_iter()
+            .rev()
+            .fold(tail, |cdr, car| Value::cons(car, cdr))
+    }
+
+    /// Everything except `nil` counts as true.
+    pub fn is_true(&self) -> bool {
+        !matches!(self, Value::Literal(Literal::Nil))
+    }
+
+    /// Collects the elements of a proper list, or returns `None` when the
+    /// value is not a `nil` terminated list.
+    pub fn to_vec(&self) -> Option<Vec<Value>> {
+        let mut values = vec![];
+        let mut rest = self;
+        loop {
+            match rest {
+                Value::Cons(cons) => {
+                    values.push(cons.car.clone());
+                    rest = &cons.cdr;
+                }
+                Value::Literal(Literal::Nil) => return Some(values),
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Unlinks the list one cons at a time, as dropping each `cdr` in turn
+/// would recurse once per element and overflow the stack on long lists.
+impl Drop for Cons {
+    fn drop(&mut self) {
+        let mut rest = std::mem::replace(&mut self.cdr, Value::Literal(Literal::Nil));
+        while let Value::Cons(cons) = rest {
+            rest = match Rc::try_unwrap(cons) {
+                Ok(mut cons) => std::mem::replace(&mut cons.cdr, Value::Literal(Literal::Nil)),
+                // The rest of the list is still shared.
+                Err(_) => break,
+            };
+        }
+    }
+}
+
+/// Walks the two lists side by side rather than recursing down the `cdr`s.
+impl PartialEq for Cons {
+    fn eq(&self, other: &Self) -> bool {
+        let (mut left, mut right) = (self, other);
+        loop {
+            if left.car != right.car {
+                return false;
+            }
+            match (&left.cdr, &right.cdr) {
+                (Value::Cons(left_cdr), Value::Cons(right_cdr)) => {
+                    if Rc::ptr_eq(left_cdr, right_cdr) {
+                        return true;
+                    }
+                    left = left_cdr;
+                    right = right_cdr;
+                }
+                (left_cdr, right_cdr) => return left_cdr == right_cdr,
+            }
+        }
+    }
 }
 
 impl std::fmt::Display for Value {
@@ -15,6 +99,25 @@ impl std::fmt::Display for Value {
         match self {
             Value::Literal(literal) => literal.fmt(f),
             Value::Symbol(_) => write!(f, "symbol"),
+            Value::Name(name) => write!(f, "{}", name),
+            Value::Cons(cons) => {
+                write!(f, "({}", cons.car)?;
+                let mut rest = &cons.cdr;
+                loop {
+                    match rest {
+                        Value::Cons(next) => {
+                            write!(f, " {}", next.car)?;
+                            rest = &next.cdr;
+                        }
+                        Value::Literal(Literal::Nil) => break,
+                        tail => {
+                            write!(f, " . {}", tail)?;
+                            break;
+                        }
+                    }
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -29,6 +132,8 @@ impl std::cmp::PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         match &[self, other] {
             [Value::Literal(left), Value::Literal(right)] => left == right,
+            [Value::Name(left), Value::Name(right)] => left == right,
+            [Value::Cons(left), Value::Cons(right)] => Rc::ptr_eq(left, right) || left == right,
             _ => false,
         }
     }
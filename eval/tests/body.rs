@@ -0,0 +1,74 @@
+mod common;
+
+use common::{eval, name, number};
+use eval::Interpreter;
+
+#[test]
+fn defun_bodies_run_every_form() {
+    assert_eq!(eval("(defun two () 1 2) (two)"), number(2));
+    assert_eq!(
+        eval("(setq n 0) (defun bump () (setq n (+ n 1)) (setq n (* n 10)) n) (bump)"),
+        number(10)
+    );
+}
+
+#[test]
+fn return_from_leaves_a_multi_form_defun() {
+    let source = "(defun classify (n)
+                    (when (<= n 0) (return-from classify 'nonpos))
+                    (return-from classify 'pos)
+                    'unreachable)";
+    assert_eq!(eval(&format!("{} (classify -3)", source)), name("nonpos"));
+    assert_eq!(eval(&format!("{} (classify 3)", source)), name("pos"));
+}
+
+#[test]
+fn lambda_and_local_function_bodies_run_every_form() {
+    assert_eq!(
+        eval("(let ((f (lambda (x) (setq x (+ x 1)) (* x 2)))) (f 3))"),
+        number(8)
+    );
+    assert_eq!(eval("(flet ((g (x) (setq x (* x 10)) (+ x 1))) (g 2))"), number(21));
+    assert_eq!(
+        eval("(labels ((g (x) (setq x (- x 1)) (if (= x 0) 0 (g x)))) (g 5))"),
+        number(0)
+    );
+}
+
+#[test]
+fn macro_bodies_run_every_form() {
+    assert_eq!(
+        eval("(defmacro twice (form) 'ignored `(progn ,form ,form)) (twice 5)"),
+        number(5)
+    );
+}
+
+#[test]
+fn empty_bodies_are_rejected() {
+    assert!(Interpreter::new().eval("(defun f ())").is_err());
+    assert!(Interpreter::new().eval("(lambda (x))").is_err());
+}
+
+#[test]
+fn nil_blocks_can_be_left_early() {
+    assert_eq!(eval("(block nil (return-from nil 1) 2)"), number(1));
+    assert_eq!(eval("(block nil 1 2)"), number(2));
+}
+
+#[test]
+fn iteration_forms_have_an_implicit_nil_block() {
+    assert_eq!(eval("(dotimes (i 10) (when (= i 3) (return-from nil i)))"), number(3));
+    assert_eq!(eval("(dolist (x '(1 2 3)) (when (> x 1) (return-from nil x)))"), number(2));
+    assert_eq!(
+        eval("(setq n 0) (while t (setq n (+ n 1)) (when (= n 5) (return-from nil n)))"),
+        number(5)
+    );
+    assert_eq!(
+        eval("(loop for i from 1 do (when (= i 4) (return-from nil (* i 10))))"),
+        number(40)
+    );
+    assert_eq!(
+        eval("(block nil (dotimes (i 3) (return-from nil 'inner)) 'outer)"),
+        name("outer")
+    );
+}
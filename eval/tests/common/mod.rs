@@ -0,0 +1,63 @@
+//! Fixtures shared by the integration tests, which each use only some of
+//! them.
+#![allow(dead_code)]
+
+use std::str::FromStr;
+
+use eval::{Interpreter, Value};
+use lisp::{BigInt, BigRational, Literal};
+
+/// Compiling to bytecode and running it on the VM, shared with the
+/// compiler's tests.
+#[path = "../../../compiler/tests/common/mod.rs"]
+pub mod compiled;
+
+pub const NIL: Value = Value::Literal(Literal::Nil);
+pub const T: Value = Value::Literal(Literal::True);
+
+/// Evaluates `source` in a fresh interpreter.
+pub fn eval(source: &str) -> Value {
+    Interpreter::new().eval(source).unwrap()
+}
+
+/// Evaluates `source` in a fresh interpreter and prints the value, for
+/// comparing lists.
+pub fn printed(source: &str) -> String {
+    eval(source).to_string()
+}
+
+/// Evaluates `source` in a fresh interpreter, returning the message of the
+/// error it must fail with.
+pub fn error(source: &str) -> String {
+    Interpreter::new().eval(source).unwrap_err().to_string()
+}
+
+pub fn number(value: i64) -> Value {
+    Value::Literal(Literal::Integer(value))
+}
+
+pub fn big(digits: &str) -> Value {
+    Value::Literal(Literal::BigInteger(BigInt::from_str(digits).unwrap()))
+}
+
+pub fn ratio(numer: i64, denom: i64) -> Value {
+    let ratio = BigRational::new(BigInt::from(numer), BigInt::from(denom));
+    Value::Literal(Literal::Ratio(Box::new(ratio)))
+}
+
+pub fn float(value: f64) -> Value {
+    Value::Literal(Literal::Float(value))
+}
+
+pub fn string(value: &str) -> Value {
+    Value::Literal(Literal::String(value.to_string()))
+}
+
+pub fn strings(values: &[&str]) -> Value {
+    Value::list(values.iter().map(|value| string(value)).collect())
+}
+
+/// A symbol, as `'name` evaluates to.
+pub fn name(value: &str) -> Value {
+    Value::Name(value.to_string())
+}
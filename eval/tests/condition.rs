@@ -0,0 +1,79 @@
+mod common;
+
+use common::{error, eval, name, number, printed, string, NIL};
+
+#[test]
+fn error_raises_its_value() {
+    assert_eq!(error("(error \"boom\")"), "boom");
+    assert_eq!(error("(error 'oops)"), "oops");
+}
+
+#[test]
+fn handler_case_runs_the_first_matching_clause() {
+    assert_eq!(eval("(handler-case (error \"boom\") (error (e) e))"), string("boom"));
+    assert_eq!(
+        eval("(handler-case (/ 1 0) (type-error () 'typed) (division-by-zero () 'divided))"),
+        name("divided")
+    );
+    assert_eq!(eval("(handler-case (+ 1 2) (error () 'failed))"), number(3));
+    assert_eq!(error("(handler-case (/ 1 0) (type-error () 'typed))"), "division by zero");
+}
+
+#[test]
+fn ignore_errors_turns_errors_into_nil() {
+    assert_eq!(eval("(ignore-errors (error \"boom\") 3)"), NIL);
+    assert_eq!(eval("(ignore-errors (/ 1 0))"), NIL);
+    assert_eq!(eval("(ignore-errors 1 2)"), number(2));
+}
+
+#[test]
+fn ignore_errors_lets_non_local_exits_through() {
+    assert_eq!(eval("(block b (ignore-errors (return-from b 9)) 1)"), number(9));
+    assert_eq!(eval("(catch 'a (ignore-errors (throw 'a 4)) 5)"), number(4));
+}
+
+#[test]
+fn throw_goes_to_the_catch_with_its_tag() {
+    assert_eq!(eval("(catch 'a (throw 'a 5) 6)"), number(5));
+    assert_eq!(eval("(catch 'a (catch 'b (throw 'a 1)) 2)"), number(1));
+    assert_eq!(eval("(defun f () (throw 'a 3)) (catch 'a (f) 5)"), number(3));
+    assert_eq!(eval("(catch 'a 7)"), number(7));
+}
+
+#[test]
+fn throw_without_a_catch_fails() {
+    assert_eq!(error("(throw 'z 1)"), "no catch for tag z");
+}
+
+#[test]
+fn unwind_protect_returns_the_protected_value() {
+    assert_eq!(
+        printed("(setq log nil) (list (unwind-protect 1 (setq log 'cleaned)) log)"),
+        "(1 cleaned)"
+    );
+}
+
+#[test]
+fn unwind_protect_cleans_up_after_non_local_exits() {
+    assert_eq!(
+        eval("(setq log nil) (catch 'a (unwind-protect (throw 'a 1) (setq log 'cleaned))) log"),
+        name("cleaned")
+    );
+    assert_eq!(
+        eval("(setq log nil) (block b (unwind-protect (return-from b 1) (setq log 'cleaned))) log"),
+        name("cleaned")
+    );
+    assert_eq!(
+        printed(
+            "(setq log nil)
+             (handler-case (unwind-protect (error \"boom\") (setq log 'cleaned))
+               (error (e) (list e log)))"
+        ),
+        "(\"boom\" cleaned)"
+    );
+}
+
+#[test]
+fn errors_in_cleanup_replace_the_result() {
+    assert_eq!(error("(unwind-protect 1 (error \"cleanup\"))"), "cleanup");
+}
@@ -0,0 +1,57 @@
+mod common;
+
+use common::{error, eval, name, number, NIL};
+
+#[test]
+fn cond_runs_the_first_true_clause() {
+    assert_eq!(eval("(cond (nil 1) ((= 1 1) 2) (t 3))"), number(2));
+    assert_eq!(eval("(cond (t 1 2 3))"), number(3));
+    assert_eq!(eval("(cond (nil 1))"), NIL);
+}
+
+#[test]
+fn cond_clauses_without_a_body_return_the_test() {
+    assert_eq!(eval("(cond (nil) ((+ 1 2)))"), number(3));
+}
+
+#[test]
+fn case_matches_keys_by_value() {
+    let classify = "(defun classify (x) (case x (1 'one) ((2 3) 'few) (otherwise 'many)))";
+    assert_eq!(eval(&format!("{} (classify 1)", classify)), name("one"));
+    assert_eq!(eval(&format!("{} (classify 3)", classify)), name("few"));
+    assert_eq!(eval(&format!("{} (classify 9)", classify)), name("many"));
+    assert_eq!(eval("(case 'b (a 1) (b 2))"), number(2));
+    assert_eq!(eval("(case 1 (t 'any))"), name("any"));
+    assert_eq!(eval("(case 5 (1 'one))"), NIL);
+}
+
+#[test]
+fn nil_case_keys_never_match() {
+    assert_eq!(eval("(case nil (nil 'empty) (otherwise 'other))"), name("other"));
+    assert_eq!(eval("(case nil ((nil) 'listed) (otherwise 'other))"), name("listed"));
+}
+
+#[test]
+fn ecase_fails_without_a_match() {
+    assert_eq!(eval("(ecase 2 (1 'one) (2 'two))"), name("two"));
+    assert_eq!(error("(ecase 3 (1 'one) (2 'two))"), "no ecase clause matches 3");
+}
+
+#[test]
+fn when_and_unless_run_their_body_on_the_condition() {
+    assert_eq!(eval("(when (= 1 1) 1 2)"), number(2));
+    assert_eq!(eval("(when nil 1)"), NIL);
+    assert_eq!(eval("(when t)"), NIL);
+    assert_eq!(eval("(unless nil 1 2)"), number(2));
+    assert_eq!(eval("(unless t 1)"), NIL);
+}
+
+#[test]
+fn malformed_clauses_report_what_was_expected() {
+    assert_eq!(error("(cond 1)"), "expected a cond clause like (test body...)");
+    assert_eq!(error("(case 1 ())"), "expected a case clause like (keys body...)");
+    assert_eq!(
+        error("(case 1 (otherwise 1) (2 2))"),
+        "otherwise must be the last case clause"
+    );
+}
@@ -0,0 +1,92 @@
+use std::thread;
+
+use eval::frame::{EVAL_STACK_MAX_DEPTH, EVAL_STACK_SIZE};
+use eval::Interpreter;
+
+/// The stack of a main thread on common platforms.
+const MAIN_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+/// Evaluates `source` on a thread with `stack_size` of stack and the given
+/// depth limit, returning the printed value or error.
+fn eval_on(stack_size: usize, max_depth: Option<usize>, source: &str) -> String {
+    let source = source.to_string();
+    thread::Builder::new()
+        .stack_size(stack_size)
+        .spawn(move || {
+            let mut interpreter = Interpreter::new();
+            if let Some(max_depth) = max_depth {
+                interpreter.context().set_max_depth(max_depth);
+            }
+            match interpreter.eval(&source) {
+                Ok(value) => value.to_string(),
+                Err(err) => format!("error: {}", err),
+            }
+        })
+        .unwrap()
+        .join()
+        .unwrap()
+}
+
+/// Evaluates `source` with the raised limit the binary runs with.
+fn eval(source: &str) -> String {
+    eval_on(EVAL_STACK_SIZE, Some(EVAL_STACK_MAX_DEPTH), source)
+}
+
+fn assert_overflows(source: &str) {
+    let result = eval(source);
+    assert!(result.starts_with("error: stack overflow"), "{}", result);
+}
+
+/// Sources that recurse 100000 levels deep through the special forms that
+/// nest evaluation.
+const DEEP_RECURSIONS: [&str; 8] = [
+    "(defun g (n) (if (= n 0) 0 (+ 1 (g (- n 1))))) (g 100000)",
+    "(defun g (n) (if (= n 0) 0 (handler-case (+ 1 (g (- n 1))) (division-by-zero () 0))))
+     (g 100000)",
+    "(defun b (n) (if (= n 0) 0 (block x (+ 1 (b (- n 1)))))) (b 100000)",
+    "(defun u (n) (if (= n 0) 0 (unwind-protect (+ 1 (u (- n 1))) 0))) (u 100000)",
+    "(defun c (n) (if (= n 0) 0 (catch 'x (+ 1 (c (- n 1)))))) (c 100000)",
+    "(defun d (n) (cond ((= n 0) 0) (t (+ 1 (d (- n 1)))))) (d 100000)",
+    "(defun l (n) (if (= n 0) 0 (let* ((z (l (- n 1)))) (+ z 1)))) (l 100000)",
+    "(defun w (n) (if (= n 0) 0 (progn (dotimes (i 1) (w (- n 1))) 1))) (w 100000)",
+];
+
+#[test]
+fn recursion_through_let_overflows_cleanly() {
+    let h = "(defun h (n) (if (= n 0) 0 (+ 1 (let ((y 1)) (+ y (h (- n 1)))))))";
+    assert_eq!(eval(&format!("{} (h 995)", h)), "1990");
+    assert_overflows(&format!("{} (h 100000)", h));
+}
+
+#[test]
+fn recursion_through_special_forms_overflows_cleanly() {
+    DEEP_RECURSIONS.iter().for_each(|source| assert_overflows(source));
+}
+
+#[test]
+fn the_default_limit_fits_a_main_thread_stack() {
+    for source in DEEP_RECURSIONS {
+        let result = eval_on(MAIN_STACK_SIZE, None, source);
+        assert!(result.starts_with("error: stack overflow"), "{}", result);
+    }
+    let h = "(defun h (n) (if (= n 0) 0 (+ 1 (let ((y 1)) (+ y (h (- n 1)))))))";
+    assert_eq!(eval_on(MAIN_STACK_SIZE, None, &format!("{} (h 150)", h)), "300");
+}
+
+#[test]
+fn stack_overflows_can_be_handled() {
+    let result = eval(
+        "(defun f (n) (+ 1 (f n)))
+         (handler-case (f 0) (stack-overflow () 'caught))",
+    );
+    assert_eq!(result, "caught");
+}
+
+#[test]
+fn tail_calls_do_not_count_toward_the_limit() {
+    let result = eval(
+        "(defun loop-down (n) (if (= n 0) 'done (loop-down (- n 1))))
+         (loop-down 100000)",
+    );
+    assert_eq!(result, "done");
+}
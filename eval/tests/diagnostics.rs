@@ -0,0 +1,35 @@
+use eval::Interpreter;
+
+#[test]
+fn errors_in_earlier_definitions_render_against_their_source() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("(defun f (x)\n  (car x))").unwrap();
+    let err = interpreter.eval("(+ 1 2)\n(f 5)").unwrap_err();
+
+    let rendered = interpreter.render(&err, "<repl>");
+    assert!(rendered.contains("--> <repl>:2:3"), "{}", rendered);
+    assert!(rendered.contains("2 |   (car x))"), "{}", rendered);
+    assert!(!rendered.contains("(f 5)"), "{}", rendered);
+}
+
+#[test]
+fn spans_from_another_source_render_without_a_caret() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("(defun f (x)\n  (car x))").unwrap();
+    let source = "(+ 1 2)\n(f 5)";
+    let err = interpreter.eval(source).unwrap_err();
+
+    let rendered = err.render("<repl>", source, 1);
+    assert!(rendered.starts_with("error: bad arguments"), "{}", rendered);
+    assert!(!rendered.contains('^'), "{}", rendered);
+}
+
+#[test]
+fn errors_in_the_current_source_point_at_it() {
+    let mut interpreter = Interpreter::new();
+    interpreter.eval("(+ 1 2)").unwrap();
+    let err = interpreter.eval("(list 1\n  (car 5))").unwrap_err();
+
+    let rendered = interpreter.render(&err, "<repl>");
+    assert!(rendered.contains("2 |   (car 5))"), "{}", rendered);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use common::{error, eval, number, printed, NIL};
+
+#[test]
+fn while_runs_until_its_condition_fails() {
+    assert_eq!(eval("(setq n 0) (while (< n 3) (setq n (+ n 1))) n"), number(3));
+    assert_eq!(eval("(while nil 1)"), NIL);
+}
+
+#[test]
+fn while_assigns_to_variables_outside_the_loop() {
+    assert_eq!(eval("(let ((n 0)) (while (< n 5) (setq n (+ n 1))) n)"), number(5));
+}
+
+#[test]
+fn variables_first_assigned_in_a_loop_stay_in_it() {
+    assert_eq!(
+        error("(setq n 0) (while (< n 3) (setq n (+ n 1)) (setq inner n)) inner"),
+        "name not found: inner"
+    );
+}
+
+#[test]
+fn dolist_binds_each_element() {
+    assert_eq!(
+        printed("(setq acc nil) (dolist (x '(1 2 3)) (setq acc (cons x acc))) acc"),
+        "(3 2 1)"
+    );
+    assert_eq!(eval("(setq acc 0) (dolist (x '(1 2 3) acc) (setq acc (+ acc x)))"), number(6));
+}
+
+#[test]
+fn dolist_binds_its_result_form_to_nil() {
+    assert_eq!(eval("(dolist (x '(1 2) x))"), NIL);
+}
+
+#[test]
+fn dotimes_counts_from_zero() {
+    assert_eq!(eval("(setq s 0) (dotimes (i 4) (setq s (+ s i))) s"), number(6));
+    assert_eq!(eval("(dotimes (i 3 i))"), number(3));
+    assert_eq!(eval("(dotimes (i -1 i))"), number(0));
+}
+
+#[test]
+fn loop_variables_shadow_outer_ones() {
+    assert_eq!(eval("(setq x 10) (dolist (x '(1 2 3)) x) x"), number(10));
+    assert_eq!(eval("(setq i 7) (dotimes (i 3) i) i"), number(7));
+    assert_eq!(eval("(setq i 5) (loop for i from 1 to 2 do (setq j i)) i"), number(5));
+}
+
+#[test]
+fn loop_collects_and_sums() {
+    assert_eq!(printed("(loop for i from 1 to 4 collect (* i i))"), "(1 4 9 16)");
+    assert_eq!(eval("(loop for x in '(1 2 3) sum x)"), number(6));
+    assert_eq!(printed("(loop for i from 1 to 3 for x in '(a b) collect x)"), "(a b)");
+    assert_eq!(eval("(setq n 0) (loop while (< n 5) do (setq n (+ n 1))) n"), number(5));
+}
+
+#[test]
+fn dolist_needs_a_list() {
+    assert_eq!(error("(dolist (x 5) x)"), "expected list, got 5");
+}
@@ -0,0 +1,50 @@
+mod common;
+
+use common::{eval, number, NIL, T};
+use eval::{Interpreter, Value};
+
+fn long_list(length: i64) -> Value {
+    Value::list((0..length).map(number).collect())
+}
+
+#[test]
+fn dropping_a_long_list_does_not_overflow() {
+    drop(long_list(1_000_000));
+}
+
+#[test]
+fn dropping_a_list_keeps_its_shared_tail() {
+    let tail = long_list(3);
+    let list = Value::cons(number(-1), tail.clone());
+    drop(list);
+    assert_eq!(tail.to_vec().unwrap(), vec![number(0), number(1), number(2)]);
+}
+
+#[test]
+fn comparing_long_lists_does_not_overflow() {
+    let list = long_list(1_000_000);
+    assert_eq!(list, list.clone());
+    assert_eq!(list, long_list(1_000_000));
+    assert_ne!(list, long_list(999_999));
+    assert_ne!(list, Value::list_with_tail((0..999_999).map(number).collect(), number(0)));
+}
+
+#[test]
+fn lists_compare_by_their_elements() {
+    assert_eq!(eval("(= '(1 (2 3)) '(1 (2 3)))"), T);
+    assert_eq!(eval("(= '(1 (2 3)) '(1 (2 4)))"), NIL);
+    assert_eq!(eval("(= '(1 2) '(1 2 3))"), NIL);
+    assert_eq!(eval("(= (cons 1 2) (cons 1 2))"), T);
+}
+
+#[test]
+fn the_interpreter_handles_long_lists() {
+    let mut interpreter = Interpreter::new();
+    let result = interpreter.eval(
+        "(setq a (loop for i from 1 to 50000 collect i))
+         (setq b (loop for i from 1 to 50000 collect i))
+         (and (= a a) (= a b))",
+    );
+    assert_eq!(result.unwrap(), T);
+    drop(interpreter);
+}
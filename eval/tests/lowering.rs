@@ -0,0 +1,90 @@
+mod common;
+
+use common::compiled::{compile, number};
+use eval::{Interpreter, Value};
+use lisp::Literal;
+use vm::bytecode;
+use vm::vm::execute;
+
+/// The result of evaluating `source` with the tree-walking evaluator, as
+/// the VM would represent it, or `None` when it fails or gives a value the
+/// VM has no equivalent for.
+fn interpret(source: &str) -> Option<bytecode::Value> {
+    match Interpreter::new().eval(source).ok()? {
+        Value::Literal(Literal::Integer(value)) => Some(bytecode::Value::Literal(value)),
+        Value::Literal(Literal::Nil) => Some(bytecode::Value::Boolean(false)),
+        _ => None,
+    }
+}
+
+/// The result of compiling `source` and running it on the VM, or `None`
+/// when it does not compile.
+fn run(source: &str) -> Option<bytecode::Value> {
+    let mut vm = compile(source).ok()?;
+    execute::run(&mut vm).unwrap();
+    Some(vm.peek().unwrap())
+}
+
+/// Checks that both backends agree on `source`, and returns the result.
+fn agree(source: &str) -> Option<bytecode::Value> {
+    let result = run(source);
+    assert_eq!(result, interpret(source), "backends disagree on {}", source);
+    result
+}
+
+#[test]
+fn dotimes_runs_its_body_and_result() {
+    assert_eq!(agree("(setq s 0) (dotimes (i 4 s) (setq s (+ s i)))"), Some(number(6)));
+    assert_eq!(agree("(setq s 0) (dotimes (i 3) (setq s (+ s 1))) s"), Some(number(3)));
+    assert_eq!(agree("(dotimes (i 3 i) 1)"), Some(number(3)));
+    assert_eq!(agree("(dotimes (i 0 7) 1)"), Some(number(7)));
+}
+
+#[test]
+fn loop_sums_and_stops() {
+    assert_eq!(agree("(loop for i from 1 to 4 sum i)"), Some(number(10)));
+    assert_eq!(agree("(setq s 0) (loop for i from 1 to 3 do (setq s (+ s i))) s"), Some(number(6)));
+    assert_eq!(agree("(setq n 0) (loop while (< n 5) do (setq n (+ n 1))) n"), Some(number(5)));
+}
+
+#[test]
+fn loop_variables_do_not_leak() {
+    assert_eq!(agree("(dotimes (i 3) 1) i"), None);
+    assert_eq!(agree("(loop for i from 1 to 3 sum i) i"), None);
+}
+
+#[test]
+fn loop_variables_shadow_and_restore_outer_bindings() {
+    assert_eq!(agree("(setq i 10) (dotimes (i 3) 1) i"), Some(number(10)));
+    assert_eq!(agree("(setq i 10) (loop for i from 1 to 3 sum i) i"), Some(number(10)));
+    assert_eq!(
+        agree("(setq s 0) (dotimes (i 3) (dotimes (j 2) (setq s (+ s i)))) s"),
+        Some(number(6))
+    );
+}
+
+#[test]
+fn locals_after_a_loop_get_fresh_slots() {
+    assert_eq!(agree("(dotimes (i 2) 1) (setq k 5) (dotimes (j 3) 1) k"), Some(number(5)));
+    assert_eq!(
+        agree("(defun f (n) (dotimes (i n) 1) (setq m (* n 2)) m) (f 4)"),
+        Some(number(8))
+    );
+}
+
+#[test]
+fn only_whole_divisions_are_lowered() {
+    assert_eq!(agree("(/ 8 2)"), Some(number(4)));
+    assert_eq!(agree("(/ 60 2 5)"), Some(number(6)));
+    assert_eq!(agree("(/ -9 3)"), Some(number(-3)));
+    assert_eq!(agree("(/ 7 2)"), None);
+    assert_eq!(agree("(/ 1 0)"), None);
+    assert_eq!(run("(defun half (x) (/ x 2)) (half 4)"), None);
+}
+
+#[test]
+fn overflowing_literal_arithmetic_is_not_lowered() {
+    assert_eq!(agree("(+ 9223372036854775807 1)"), None);
+    assert_eq!(agree("(* 9223372036854775807 2)"), None);
+    assert_eq!(agree("(- 9223372036854775807 1)"), Some(number(i64::MAX - 1)));
+}
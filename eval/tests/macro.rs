@@ -0,0 +1,40 @@
+mod common;
+
+use common::{error, eval, number, printed};
+
+const MACROS: &str = "
+    (defmacro my-when (condition &body body) `(if ,condition (progn ,@body) nil))
+    (defmacro twice-when (condition form) `(my-when ,condition ,form ,form))";
+
+fn with_macros(source: &str) -> String {
+    printed(&format!("{} {}", MACROS, source))
+}
+
+#[test]
+fn macros_expand_into_the_code_they_build() {
+    assert_eq!(with_macros("(my-when t 1 2)"), "2");
+    assert_eq!(with_macros("(my-when nil 1 2)"), "NIL");
+    assert_eq!(with_macros("(setq n 0) (twice-when t (setq n (+ n 1))) n"), "2");
+}
+
+#[test]
+fn macroexpand_1_expands_once() {
+    assert_eq!(with_macros("(macroexpand-1 '(twice-when t 1))"), "(my-when T 1 1)");
+}
+
+#[test]
+fn macroexpand_expands_until_the_form_is_not_a_macro_call() {
+    assert_eq!(with_macros("(macroexpand '(twice-when t 1))"), "(if T (progn 1 1) NIL)");
+}
+
+#[test]
+fn forms_that_are_not_macro_calls_expand_to_themselves() {
+    assert_eq!(with_macros("(macroexpand '(+ 1 2))"), "(+ 1 2)");
+    assert_eq!(with_macros("(macroexpand-1 '(list 1))"), "(list 1)");
+    assert_eq!(eval("(macroexpand-1 5)"), number(5));
+}
+
+#[test]
+fn expanding_with_the_wrong_arguments_fails() {
+    assert_eq!(error(&format!("{} (macroexpand-1 '(my-when))", MACROS)), "bad arguments");
+}
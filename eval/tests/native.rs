@@ -0,0 +1,75 @@
+mod common;
+
+use common::{float, number, string, NIL, T};
+use eval::base::EvalError;
+use eval::{Interpreter, Value};
+
+fn interpreter() -> Interpreter {
+    let mut interpreter = Interpreter::new();
+    interpreter.register_function("half", |x: f64| x / 2.0);
+    interpreter.register_function("shout", |s: String| format!("{}!", s.to_uppercase()));
+    interpreter.register_function("both", |a: bool, b: bool| a && b);
+    interpreter.register_function("total", |numbers: Vec<f64>| numbers.iter().sum::<f64>());
+    interpreter.register_function("or-zero", |x: Option<f64>| x.unwrap_or(0.0));
+    interpreter.register_function("words", |s: String| {
+        s.split(' ').map(String::from).collect::<Vec<String>>()
+    });
+    interpreter.register_function("checked", |x: f64| match x >= 0.0 {
+        true => Ok(x),
+        false => Err(EvalError::User(string("negative"))),
+    });
+    interpreter
+}
+
+fn eval(source: &str) -> Value {
+    interpreter().eval(source).unwrap()
+}
+
+fn error(source: &str) -> String {
+    interpreter().eval(source).unwrap_err().to_string()
+}
+
+#[test]
+fn arguments_and_results_are_converted() {
+    assert_eq!(eval("(half 3)"), float(1.5));
+    assert_eq!(eval(r#"(shout "hi")"#), string("HI!"));
+    assert_eq!(eval("(both 1 t)"), T);
+    assert_eq!(eval("(both 1 nil)"), NIL);
+    assert_eq!(eval("(total (list 1 2 3.5))"), float(6.5));
+    assert_eq!(eval("(total nil)"), float(0.0));
+    assert_eq!(eval("(or-zero nil)"), float(0.0));
+    assert_eq!(eval("(or-zero 4)"), float(4.0));
+    assert_eq!(eval(r#"(length (words "a b c"))"#), number(3));
+    assert_eq!(eval(r#"(car (words "a b"))"#), string("a"));
+}
+
+#[test]
+fn wrong_argument_counts_fail_before_the_closure_runs() {
+    assert_eq!(error("(half)"), "bad arguments");
+    assert_eq!(error("(half 1 2)"), "bad arguments");
+}
+
+#[test]
+fn wrong_argument_types_are_reported() {
+    assert_eq!(error(r#"(half "x")"#), r#"expected number, got "x""#);
+    assert_eq!(error("(shout 5)"), "expected string, got 5");
+    assert_eq!(error("(total 5)"), "expected list, got 5");
+    assert_eq!(error(r#"(total (list 1 "x"))"#), r#"expected number, got "x""#);
+}
+
+#[test]
+fn closures_can_fail() {
+    assert_eq!(eval("(checked 2)"), float(2.0));
+    assert_eq!(error("(checked -2)"), "negative");
+}
+
+#[test]
+fn registering_again_replaces_the_function() {
+    let mut interpreter = interpreter();
+    interpreter.register_function("half", |x: i64| x / 2);
+    assert_eq!(interpreter.eval("(half 7)").unwrap(), number(3));
+    assert_eq!(
+        interpreter.eval("(handler-case (half 1.5) (type-error () 0))").unwrap(),
+        number(0)
+    );
+}
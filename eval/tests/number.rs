@@ -0,0 +1,84 @@
+mod common;
+
+use common::{big, error, eval, float, number, ratio, NIL, T};
+
+#[test]
+fn overflowing_integers_become_bignums() {
+    assert_eq!(eval("(+ 9223372036854775807 1)"), big("9223372036854775808"));
+    assert_eq!(eval("(- -9223372036854775808 1)"), big("-9223372036854775809"));
+    assert_eq!(eval("(* 9223372036854775807 2)"), big("18446744073709551614"));
+    assert_eq!(eval("(abs -9223372036854775808)"), big("9223372036854775808"));
+    assert_eq!(eval("(expt 2 64)"), big("18446744073709551616"));
+}
+
+#[test]
+fn bignums_that_fit_become_integers_again() {
+    assert_eq!(eval("(- (+ 9223372036854775807 1) 1)"), number(i64::MAX));
+    assert_eq!(eval("(/ (expt 2 64) (expt 2 62))"), number(4));
+}
+
+#[test]
+fn division_normalises_ratios() {
+    assert_eq!(eval("(/ 4 2)"), number(2));
+    assert_eq!(eval("(/ 2 4)"), ratio(1, 2));
+    assert_eq!(eval("(/ 6 -4)"), ratio(-3, 2));
+    assert_eq!(eval("(/ -6 -4)"), ratio(3, 2));
+    assert_eq!(eval("(/ 1 2 3)"), ratio(1, 6));
+    assert_eq!(eval("(+ (/ 1 2) (/ 1 2))"), number(1));
+    assert_eq!(eval("(* (/ 2 3) 3)"), number(2));
+}
+
+#[test]
+fn ratios_print_in_lowest_terms() {
+    assert_eq!(eval("(/ 2 -4)").to_string(), "-1/2");
+    assert_eq!(eval("(/ -10 -4)").to_string(), "5/2");
+}
+
+#[test]
+fn dividing_by_zero_fails() {
+    assert_eq!(error("(/ 1 0)"), "division by zero");
+    assert_eq!(error("(/ 1.5 0)"), "division by zero");
+    assert_eq!(error("(/ 1 (/ 0 2))"), "division by zero");
+    assert_eq!(error("(mod 5 0)"), "division by zero");
+    assert_eq!(error("(expt 0 -1)"), "division by zero");
+    assert_eq!(eval("(handler-case (/ 1 0) (division-by-zero () 0))"), number(0));
+}
+
+#[test]
+fn expt_stays_exact() {
+    assert_eq!(eval("(expt 2 10)"), number(1024));
+    assert_eq!(eval("(expt 2 -2)"), ratio(1, 4));
+    assert_eq!(eval("(expt (/ 2 3) 2)"), ratio(4, 9));
+    assert_eq!(eval("(expt -1 9999999999)"), number(-1));
+    assert_eq!(eval("(expt 1 -9999999999)"), number(1));
+    assert_eq!(eval("(expt 0 9999999999)"), number(0));
+}
+
+#[test]
+fn expt_with_a_huge_exponent_fails() {
+    assert_eq!(error("(expt 2 2000000)"), "number too large");
+    assert_eq!(error("(expt 10 9999999999)"), "number too large");
+    assert_eq!(error("(expt (/ 1 3) -2000000)"), "number too large");
+    assert_eq!(
+        eval("(handler-case (expt 2 2000000) (arithmetic-error () 0))"),
+        number(0)
+    );
+    assert_eq!(eval("(= (expt 2 100000) (* (expt 2 50000) (expt 2 50000)))"), T);
+}
+
+#[test]
+fn floats_can_have_exponents() {
+    assert_eq!(eval("(truncate 1e3)"), number(1000));
+    assert_eq!(eval("(* 2 2.5e-1)"), float(0.5));
+    assert_eq!(eval("(parse-number \"2E2\")"), float(200.0));
+    assert_eq!(eval("(parse-number \"1e999\")"), NIL);
+    assert_eq!(eval("(parse-number \"inf\")"), NIL);
+}
+
+#[test]
+fn printed_floats_evaluate_back_to_themselves() {
+    for source in ["(* 1e10 1e10)", "(/ 1.0 3)", "(expt 2.0 -30)", "1.5e300"] {
+        let value = eval(source);
+        assert_eq!(eval(&value.to_string()), value, "{} should read back", value);
+    }
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{error, eval, name, printed, string};
+
+#[test]
+fn quote_returns_its_argument_unevaluated() {
+    assert_eq!(eval("'x"), name("x"));
+    assert_eq!(eval("(quote x)"), name("x"));
+    assert_eq!(eval("'\"s\""), string("s"));
+    assert_eq!(printed("'(a (b 1) \"s\" nil)"), "(a (b 1) \"s\" NIL)");
+    assert_eq!(printed("''a"), "(quote a)");
+}
+
+#[test]
+fn quasiquote_without_unquotes_is_a_quote() {
+    assert_eq!(eval("`x"), name("x"));
+    assert_eq!(printed("`(a (b c))"), "(a (b c))");
+}
+
+#[test]
+fn unquote_evaluates_its_form() {
+    assert_eq!(printed("(setq b 2) `(a ,b ,(+ b 1))"), "(a 2 3)");
+    assert_eq!(printed("(setq b 2) `(a (b ,b))"), "(a (b 2))");
+}
+
+#[test]
+fn unquote_splicing_inserts_the_elements_of_a_list() {
+    assert_eq!(printed("(setq c '(3 4)) `(a ,@c e)"), "(a 3 4 e)");
+    assert_eq!(printed("(setq c '(3 4)) `(a (,@c))"), "(a (3 4))");
+    assert_eq!(printed("`(a ,@nil b)"), "(a b)");
+}
+
+#[test]
+fn unquote_splicing_needs_a_list() {
+    assert_eq!(error("`(1 ,@5)"), "bad arguments");
+}
+
+#[test]
+fn unquote_outside_a_quasiquote_fails() {
+    assert_eq!(error(",b"), "unexpected ,");
+}
@@ -0,0 +1,61 @@
+mod common;
+
+use std::rc::Rc;
+
+use common::{eval, number, T};
+use eval::value::Value;
+use eval::Interpreter;
+
+#[test]
+fn bindings_shadow_builtins() {
+    assert_eq!(eval("(let ((list '(1 2))) (car list))"), number(1));
+    assert_eq!(eval("(defun f (list) (length list)) (f '(1 2 3))"), number(3));
+    assert_eq!(eval("(let* ((car 5) (cdr (+ car 1))) cdr)"), number(6));
+    assert_eq!(eval("(let ((f (lambda (reverse) reverse))) (f 7))"), number(7));
+}
+
+#[test]
+fn builtins_are_visible_again_outside_the_binding() {
+    assert_eq!(eval("(let ((list 1)) list) (car (list 4 5))"), number(4));
+}
+
+#[test]
+fn closures_see_their_defining_scope() {
+    assert_eq!(
+        eval("(defun adder (n) (lambda (x) (+ x n))) (let ((add (adder 2))) (add 3))"),
+        number(5)
+    );
+}
+
+#[test]
+fn the_innermost_binding_wins_across_variables_and_functions() {
+    assert_eq!(eval("(flet ((f () 1)) (let ((f 2)) f))"), number(2));
+    assert_eq!(eval("(let ((g 5)) (flet ((g () 7)) (g)))"), number(7));
+    assert_eq!(eval("(defun h () 1) (let ((h 2)) h)"), number(2));
+    assert_eq!(eval("(defun k () 1) (flet ((k () 2)) (k))"), number(2));
+}
+
+const EVEN_ODD: &str = "
+    (labels ((is-even (n) (if (= n 0) t (is-odd (- n 1))))
+             (is-odd (n) (if (= n 0) nil (is-even (- n 1)))))";
+
+#[test]
+fn labels_functions_can_call_each_other() {
+    assert_eq!(eval(&format!("{} (is-even 10))", EVEN_ODD)), T);
+}
+
+#[test]
+fn labels_functions_work_after_their_form_exits() {
+    assert_eq!(
+        eval(&format!("(defun make () {} is-odd)) (let ((odd (make))) (odd 7))", EVEN_ODD)),
+        T
+    );
+}
+
+#[test]
+fn labels_functions_do_not_keep_their_frame_alive() {
+    match Interpreter::new().eval("(labels ((f () 1)) f)").unwrap() {
+        Value::Symbol(function) => assert_eq!(Rc::strong_count(&function), 1),
+        value => panic!("expected a function, got {}", value),
+    }
+}
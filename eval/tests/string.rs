@@ -0,0 +1,88 @@
+mod common;
+
+use common::{error, eval, number, string, strings, NIL, T};
+use eval::Interpreter;
+
+#[test]
+fn subseq_takes_a_range_of_characters() {
+    assert_eq!(eval("(subseq \"hello\" 1 3)"), string("el"));
+    assert_eq!(eval("(subseq \"hello\" 2)"), string("llo"));
+    assert_eq!(eval("(subseq \"hello\" 5)"), string(""));
+    assert_eq!(eval("(subseq \"hello\" 0 5)"), string("hello"));
+    assert_eq!(eval("(subseq \"hello\" 2 nil)"), string("llo"));
+}
+
+#[test]
+fn subseq_reports_the_string_length_when_out_of_range() {
+    assert_eq!(error("(subseq \"hello\" 6)"), "index 6 is out of bounds for length 5");
+    assert_eq!(error("(subseq \"hello\" 7 2)"), "index 7 is out of bounds for length 5");
+    assert_eq!(error("(subseq \"hello\" 0 9)"), "index 9 is out of bounds for length 5");
+    assert_eq!(error("(subseq \"hello\" 3 2)"), "start 3 is after end 2");
+}
+
+#[test]
+fn negative_indices_are_out_of_range() {
+    assert_eq!(error("(subseq \"hello\" -1)"), "index -1 is out of bounds for length 5");
+    assert_eq!(error("(subseq \"hello\" 0 -2)"), "index -2 is out of bounds for length 5");
+    assert_eq!(error("(char \"hello\" -1)"), "index -1 is out of bounds for length 5");
+}
+
+#[test]
+fn char_indexes_characters() {
+    assert_eq!(eval("(char \"hello\" 0)"), string("h"));
+    assert_eq!(eval("(char \"hello\" 4)"), string("o"));
+    assert_eq!(error("(char \"hello\" 5)"), "index 5 is out of bounds for length 5");
+}
+
+#[test]
+fn indices_and_lengths_count_multibyte_characters() {
+    assert_eq!(eval("(string-length \"日本語\")"), number(3));
+    assert_eq!(eval("(subseq \"héllo\" 1 3)"), string("él"));
+    assert_eq!(eval("(subseq \"日本語\" 2)"), string("語"));
+    assert_eq!(eval("(char \"héllo\" 1)"), string("é"));
+    assert_eq!(eval("(search \"l\" \"héllo\")"), number(2));
+    assert_eq!(eval("(split \"日本\" \"\")"), strings(&["日", "本"]));
+    assert_eq!(error("(subseq \"日本語\" 4)"), "index 4 is out of bounds for length 3");
+}
+
+#[test]
+fn case_conversion() {
+    assert_eq!(eval("(string-upcase \"Hello\")"), string("HELLO"));
+    assert_eq!(eval("(string-downcase \"HeLLo\")"), string("hello"));
+    assert_eq!(eval("(string-upcase \"straße\")"), string("STRASSE"));
+}
+
+#[test]
+fn trimming_splitting_and_joining() {
+    assert_eq!(eval("(string-trim \"  hi \")"), string("hi"));
+    assert_eq!(eval("(string-trim \"ab\" \"abxba\")"), string("x"));
+    assert_eq!(eval("(split \" a  b \")"), strings(&["a", "b"]));
+    assert_eq!(eval("(split \"a,b,\" \",\")"), strings(&["a", "b", ""]));
+    assert_eq!(eval("(join (list \"a\" \"b\"))"), string("a b"));
+    assert_eq!(eval("(join (list \"a\" 1 \"b\") \"-\")"), string("a-1-b"));
+    assert_eq!(eval("(concatenate \"a\" 1 \"b\")"), string("a1b"));
+}
+
+#[test]
+fn searching_and_comparing() {
+    assert_eq!(eval("(search \"lo\" \"hello\")"), number(3));
+    assert_eq!(eval("(search \"x\" \"hello\")"), NIL);
+    assert_eq!(eval("(string= \"a\" \"a\")"), T);
+    assert_eq!(eval("(string< \"a\" \"b\")"), T);
+    assert_eq!(eval("(string< \"b\" \"a\")"), NIL);
+    assert_eq!(eval("(string-replace \"aXbX\" \"X\" \"-\")"), string("a-b-"));
+    assert_eq!(error("(string-replace \"abc\" \"\" \"-\")"), "expected non-empty string, got \"\"");
+}
+
+#[test]
+fn numbers_convert_to_and_from_strings() {
+    assert_eq!(eval("(parse-number \" 42 \")"), number(42));
+    assert_eq!(eval("(parse-number \"x\")"), NIL);
+    assert_eq!(eval("(number-to-string (/ 1 2))"), string("1/2"));
+}
+
+#[test]
+fn non_strings_are_rejected() {
+    assert!(Interpreter::new().eval("(subseq 5 0)").is_err());
+    assert!(Interpreter::new().eval("(string-length 'a)").is_err());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::{eval, number, string, NIL, T};
+
+#[test]
+fn if_treats_everything_but_nil_as_true() {
+    assert_eq!(eval(r#"(if 1 "yes" "no")"#), string("yes"));
+    assert_eq!(eval(r#"(if 0 "yes" "no")"#), string("yes"));
+    assert_eq!(eval(r#"(if "x" "yes" "no")"#), string("yes"));
+    assert_eq!(eval(r#"(if "" "yes" "no")"#), string("yes"));
+    assert_eq!(eval(r#"(if (list 1) "yes" "no")"#), string("yes"));
+    assert_eq!(eval(r#"(if t "yes" "no")"#), string("yes"));
+    assert_eq!(eval(r#"(if nil "yes" "no")"#), string("no"));
+    assert_eq!(eval(r#"(if (list) "yes" "no")"#), string("no"));
+    assert_eq!(eval(r#"(if nil "yes")"#), NIL);
+}
+
+#[test]
+fn not_inverts_truthiness() {
+    assert_eq!(eval("(not nil)"), T);
+    assert_eq!(eval("(not 0)"), NIL);
+    assert_eq!(eval(r#"(not "x")"#), NIL);
+    assert_eq!(eval("(not t)"), NIL);
+}
+
+#[test]
+fn and_returns_the_deciding_value() {
+    assert_eq!(eval("(and)"), T);
+    assert_eq!(eval("(and 1 2 3)"), number(3));
+    assert_eq!(eval("(and 1 nil 3)"), NIL);
+    assert_eq!(eval("(and nil (undefined))"), NIL);
+}
+
+#[test]
+fn or_returns_the_deciding_value() {
+    assert_eq!(eval("(or)"), NIL);
+    assert_eq!(eval("(or 0 nil)"), number(0));
+    assert_eq!(eval(r#"(or nil "x")"#), string("x"));
+    assert_eq!(eval("(or nil nil)"), NIL);
+    assert_eq!(eval("(or 1 (undefined))"), number(1));
+}
@@ -1,14 +1,39 @@
 use std::fmt;
+use std::rc::Rc;
+
+pub use num_bigint::BigInt;
+pub use num_rational::BigRational;
+
+/// A region of source text. `offset` and `length` are in bytes, `line` and
+/// `column` point at the first character and count from 1. `source` tells
+/// apart the texts lexed by one program, since spans in a function body
+/// outlive the input that defined it.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Span {
+    pub source: usize,
+    pub offset: usize,
+    pub length: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub span: Span,
+}
 
 #[derive(Clone, Debug, PartialEq)]
-pub enum Token {
+pub enum TokenKind {
     OpenParen,
     CloseParen,
     Atom(Atom),
     Defun,
     Lambda,
+    Quote,
     Backtick,
     Comma,
+    CommaAt,
     If,
 }
 
@@ -21,13 +46,26 @@ pub enum Atom {
 #[derive(Clone, Debug, PartialEq)]
 pub enum Literal {
     String(String),
-    Number(f64),
+    /// An integer small enough for an `i64`. Larger ones are `BigInteger`s.
+    Integer(i64),
+    BigInteger(BigInt),
+    /// A ratio in lowest terms whose denominator isn't 1. Boxed as it is
+    /// much larger than the other variants.
+    Ratio(Box<BigRational>),
+    Float(f64),
     True,
     Nil
 }
 
 #[derive(Clone, Debug)]
-pub enum Expression {
+pub struct Expression {
+    /// Shared, so that cloning an expression doesn't copy the tree below it.
+    pub kind: Rc<ExpressionKind>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExpressionKind {
     Literal(Literal),
     Name(String),
     Invoke(String, Vec<Token>),
@@ -35,6 +73,46 @@ pub enum Expression {
     Operator(Box<Operator>)
 }
 
+#[derive(Clone, Debug)]
+pub enum Datum {
+    Literal(Literal),
+    Name(String),
+    List(Vec<Datum>),
+    Unquote(Box<Expression>),
+    UnquoteSplicing(Box<Expression>),
+}
+
+/// A `(name value)` pair bound by `let` and `let*`.
+pub type Binding = (String, Expression);
+
+/// A `(name (parameters) body)` definition bound by `flet` and `labels`.
+pub type LocalFunction = (String, Vec<String>, Expression);
+
+/// A `(type (variable) body)` clause of `handler-case`. The variable is
+/// optional.
+pub type ErrorHandler = (String, Option<String>, Vec<Expression>);
+
+/// A `(test body)` clause of `cond`. An empty body returns the test's value.
+pub type CondClause = (Expression, Vec<Expression>);
+
+/// A `(keys body)` clause of `case` and `ecase`. The keys are `None` for the
+/// `otherwise` clause.
+pub type CaseClause = (Option<Vec<Datum>>, Vec<Expression>);
+
+/// A clause of the `loop` subset. `for` and `while` clauses end the loop,
+/// the others run once per iteration, all in the order written.
+#[derive(Clone, Debug)]
+pub enum LoopClause {
+    /// `for name from start [to end]`
+    ForFrom(String, Expression, Option<Expression>),
+    /// `for name in list`
+    ForIn(String, Expression),
+    While(Expression),
+    Do(Vec<Expression>),
+    Collect(Expression),
+    Sum(Expression),
+}
+
 #[derive(Clone, Debug)]
 pub enum Operator {
     SetQ(String, Expression),
@@ -42,14 +120,118 @@ pub enum Operator {
     If(Box<Expression>, Box<Expression>, Option<Box<Expression>>),
     Function(String, Vec<String>, Box<Expression>),
     Lambda(Vec<String>, Box<Expression>),
-    // Let(Vec<(String, Expression)>),
+    Quote(Datum),
+    Quasiquote(Datum),
+    Macro(String, Vec<String>, Box<Expression>),
+    MacroCall(String, Vec<Datum>),
+    Let(Vec<Binding>, Vec<Expression>),
+    LetStar(Vec<Binding>, Vec<Expression>),
+    FLet(Vec<LocalFunction>, Vec<Expression>),
+    Labels(Vec<LocalFunction>, Vec<Expression>),
+    And(Vec<Expression>),
+    Or(Vec<Expression>),
+    Cond(Vec<CondClause>),
+    Case(Box<Expression>, Vec<CaseClause>),
+    ECase(Box<Expression>, Vec<CaseClause>),
+    When(Box<Expression>, Vec<Expression>),
+    Unless(Box<Expression>, Vec<Expression>),
+    HandlerCase(Box<Expression>, Vec<ErrorHandler>),
+    IgnoreErrors(Vec<Expression>),
+    UnwindProtect(Box<Expression>, Vec<Expression>),
+    Block(String, Vec<Expression>),
+    ReturnFrom(String, Option<Box<Expression>>),
+    Catch(Box<Expression>, Vec<Expression>),
+    Throw(Box<Expression>, Box<Expression>),
+    While(Box<Expression>, Vec<Expression>),
+    DoTimes(String, Box<Expression>, Option<Box<Expression>>, Vec<Expression>),
+    DoList(String, Box<Expression>, Option<Box<Expression>>, Vec<Expression>),
+    Loop(Vec<LoopClause>),
+}
+
+impl Span {
+    /// Returns the span running from the start of `self` to the end of `end`.
+    pub fn to(&self, end: Span) -> Span {
+        Span {
+            length: (end.offset + end.length).saturating_sub(self.offset),
+            ..*self
+        }
+    }
+}
+
+impl Token {
+    pub fn new(kind: TokenKind, span: Span) -> Self {
+        Self { kind, span }
+    }
+}
+
+impl Literal {
+    /// Makes an integer literal, as an `Integer` when it fits in an `i64`.
+    pub fn integer(integer: BigInt) -> Self {
+        match i64::try_from(&integer) {
+            Ok(integer) => Literal::Integer(integer),
+            Err(_) => Literal::BigInteger(integer),
+        }
+    }
+
+    /// Makes a ratio literal, or an integer one when the ratio is whole.
+    pub fn ratio(ratio: BigRational) -> Self {
+        match ratio.is_integer() {
+            true => Literal::integer(ratio.to_integer()),
+            false => Literal::Ratio(Box::new(ratio)),
+        }
+    }
+}
+
+impl Expression {
+    pub fn new(kind: ExpressionKind, span: Span) -> Self {
+        Self {
+            kind: Rc::new(kind),
+            span,
+        }
+    }
+}
+
+/// Quotes a string, escaping it the way the lexer reads it back.
+fn _escape(string: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in string.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\t' => escaped.push_str("\\t"),
+            '\r' => escaped.push_str("\\r"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Formats a float the way the lexer reads it back, always with a `.` to
+/// mark it as a float: `1.0`, `0.25` or `1.0e20`. Infinities and NaN have
+/// no literal syntax and print as `inf` and `NaN`.
+fn _format_float(float: f64) -> String {
+    // Debug formatting keeps the `.0` of a whole float, but not before an
+    // exponent.
+    let debug = format!("{:?}", float);
+    match debug.split_once('e') {
+        Some((mantissa, exponent)) if !mantissa.contains('.') => {
+            format!("{}.0e{}", mantissa, exponent)
+        }
+        _ => debug,
+    }
 }
 
 impl std::fmt::Display for Literal {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let str = match self {
-            Literal::String(str) => format!("\"{}\"", str),
-            Literal::Number(num) => num.to_string(),
+            Literal::String(str) => _escape(str),
+            Literal::Integer(num) => num.to_string(),
+            Literal::BigInteger(num) => num.to_string(),
+            Literal::Ratio(ratio) => ratio.to_string(),
+            Literal::Float(num) => _format_float(*num),
             Literal::True => String::from("T"),
             Literal::Nil => String::from("NIL")
         };
@@ -57,3 +239,21 @@ impl std::fmt::Display for Literal {
     }
 }
 
+
+impl std::fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TokenKind::OpenParen => write!(f, "'('"),
+            TokenKind::CloseParen => write!(f, "')'"),
+            TokenKind::Atom(Atom::Literal(literal)) => write!(f, "{}", literal),
+            TokenKind::Atom(Atom::Name(name)) => write!(f, "{}", name),
+            TokenKind::Defun => write!(f, "defun"),
+            TokenKind::Lambda => write!(f, "lambda"),
+            TokenKind::Quote => write!(f, "'"),
+            TokenKind::Backtick => write!(f, "`"),
+            TokenKind::Comma => write!(f, ","),
+            TokenKind::CommaAt => write!(f, ",@"),
+            TokenKind::If => write!(f, "if"),
+        }
+    }
+}
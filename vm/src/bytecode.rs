@@ -5,6 +5,13 @@ pub enum Value {
     Reference(usize)
 }
 
+impl Value {
+    /// Only `false`, which `nil` compiles to, counts as false.
+    pub fn is_true(&self) -> bool {
+        !matches!(self, Value::Boolean(false))
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum Opcode {
     Noop,
@@ -16,6 +23,8 @@ pub enum Opcode {
 
     Push(Value),
     PushLocal(usize),
+    /// Pops a value into a local, growing the frame's locals if needed.
+    SetLocal(usize),
     Pop,
 
     Jump,
@@ -23,5 +32,9 @@ pub enum Opcode {
     JumpFalse,
     Call(usize),
     Return(usize),
-    Compare
+    Compare,
+    CompareLess,
+    CompareGreater,
+    CompareLessEq,
+    CompareGreaterEq,
 }
@@ -1,8 +1,7 @@
-mod bytecode;
-mod vm;
-
-use bytecode::{Opcode, Value};
-use vm::execute::ExecuteError;
+use vm::bytecode::{Opcode, Value};
+use vm::vm::code::CodeVector;
+use vm::vm::execute::{execute, ExecuteError};
+use vm::vm::Vm;
 
 fn main() {
     let first_chunk = vec![
@@ -48,16 +47,16 @@ fn main() {
         Opcode::Return(0),
     ]);
 
-    let context = vm::code::CodeVector::new(vec![
+    let context = CodeVector::new(vec![
         first_chunk.clone(),
         second_chunk.clone(),
         third_chunk.clone(),
     ]);
 
-    let mut vm = vm::Vm::new(context);
+    let mut vm = Vm::new(context);
 
     loop {
-        match vm::execute::execute(&mut vm) {
+        match execute(&mut vm) {
             Ok(()) => {}
             Err(ExecuteError::NoOpcode) => {
                 println!("call_stack = {:?}", vm.call_stack);
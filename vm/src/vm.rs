@@ -2,6 +2,7 @@ mod call_stack;
 pub mod code;
 mod data_stack;
 pub mod execute;
+#[allow(clippy::module_inception)]
 mod vm;
 
 pub use vm::Vm;
@@ -2,9 +2,13 @@ use std::collections::VecDeque;
 
 use crate::bytecode::Value;
 
-use super::code::CodePtr;
+use super::code::{CodePtr, CodeVector};
 use super::execute::{ExecuteError, ExecuteResult};
 
+/// How many frames may be on the call stack before a call fails with
+/// `ExecuteError::StackOverflow`.
+pub const DEFAULT_MAX_DEPTH: usize = 10_000;
+
 #[derive(Clone, Debug)]
 pub struct CallStackFrame {
     locals: Vec<Value>,
@@ -15,6 +19,7 @@ pub struct CallStackFrame {
 #[derive(Clone, Debug)]
 pub struct CallStack {
     frames: VecDeque<CallStackFrame>,
+    max_depth: usize,
 }
 
 impl CallStackFrame {
@@ -26,6 +31,13 @@ impl CallStackFrame {
         &self.locals[..]
     }
 
+    pub fn set_local(&mut self, index: usize, value: Value) {
+        if index >= self.locals.len() {
+            self.locals.resize(index + 1, Value::Boolean(false));
+        }
+        self.locals[index] = value;
+    }
+
     pub fn get_code_ptr(&self) -> &CodePtr {
         &self.code_ptr
     }
@@ -39,9 +51,14 @@ impl CallStack {
     pub fn new() -> Self {
         CallStack {
             frames: VecDeque::from([CallStackFrame::new(CodePtr::new(0, 0), Vec::new())]),
+            max_depth: DEFAULT_MAX_DEPTH,
         }
     }
 
+    pub fn set_max_depth(&mut self, max_depth: usize) {
+        self.max_depth = max_depth;
+    }
+
     pub fn peek(&self) -> ExecuteResult<&CallStackFrame> {
         self.frames.front().ok_or(ExecuteError::EmptyCallStack)
     }
@@ -50,8 +67,19 @@ impl CallStack {
         self.frames.front_mut().ok_or(ExecuteError::EmptyCallStack)
     }
 
-    pub fn push(&mut self, frame: CallStackFrame) {
-        self.frames.push_front(frame)
+    /// Pushes `frame`, failing once the depth limit is reached. `code` names
+    /// the functions on the stack in the error.
+    pub fn push(&mut self, frame: CallStackFrame, code: &CodeVector) -> ExecuteResult<()> {
+        if self.frames.len() >= self.max_depth {
+            let names = std::iter::once(&frame)
+                .chain(self.frames.iter())
+                .map(|frame| code.get_chunk_name(frame.code_ptr.get_chunk_id()))
+                .collect();
+            return Err(ExecuteError::StackOverflow(self.frames.len() + 1, names));
+        }
+
+        self.frames.push_front(frame);
+        Ok(())
     }
 
     pub fn pop(&mut self) -> ExecuteResult<CallStackFrame> {
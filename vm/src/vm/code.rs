@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::bytecode::Opcode;
 
 #[derive(Clone, Debug)]
@@ -26,11 +28,29 @@ impl CodePtr {
 #[derive(Debug)]
 pub struct CodeVector {
     chunks: Vec<Vec<Opcode>>,
+    /// Names of the functions the chunks were compiled from, for errors.
+    names: HashMap<usize, String>,
 }
 
 impl CodeVector {
     pub fn new(chunks: Vec<Vec<Opcode>>) -> Self {
-        Self { chunks }
+        Self {
+            chunks,
+            names: HashMap::new(),
+        }
+    }
+
+    pub fn set_chunk_name(&mut self, chunk_id: usize, name: &str) {
+        self.names.insert(chunk_id, name.to_string());
+    }
+
+    /// The name of the function in the chunk, or `chunk <id>` for chunks
+    /// without one.
+    pub fn get_chunk_name(&self, chunk_id: usize) -> String {
+        self.names
+            .get(&chunk_id)
+            .cloned()
+            .unwrap_or_else(|| format!("chunk {}", chunk_id))
     }
 
     pub fn get_chunk(&self, chunk_id: usize) -> Option<&Vec<Opcode>> {
@@ -23,7 +23,15 @@ impl DataStack {
 
         let value = self.stack[self.stack_ptr - 1];
         self.stack_ptr -= 1;
-        return Ok(value);
+        Ok(value)
+    }
+
+    pub fn peek(&self) -> ExecuteResult<Value> {
+        if self.stack_ptr == 0 {
+            return Err(ExecuteError::EmptyDataStack);
+        }
+
+        Ok(self.stack[self.stack_ptr - 1])
     }
 
     pub fn pop_ref(&mut self) -> ExecuteResult<usize> {
@@ -52,8 +60,7 @@ impl DataStack {
 
     pub fn push_many(&mut self, values: Vec<Value>) -> ExecuteResult<()> {
         values
-            .iter()
-            .map(|value| self.push(value.clone()))
-            .collect::<ExecuteResult<()>>()
+            .into_iter()
+            .try_for_each(|value| self.push(value))
     }
 }
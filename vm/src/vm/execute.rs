@@ -8,9 +8,14 @@ pub enum ExecuteError {
     EmptyCallStack,
     EmptyDataStack,
     InvalidReference,
+    /// Integer arithmetic overflowed. The VM only has `i64` integers.
+    IntegerOverflow,
     InvalidValue,
     LocalNotFound(usize),
     NoOpcode,
+    /// The call depth limit was hit. Holds the depth and the names of the
+    /// functions on the stack, innermost first.
+    StackOverflow(usize, Vec<String>),
     UnhandledOpcode(Opcode),
     ZeroDivision,
 }
@@ -23,7 +28,7 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
         }
         Opcode::JumpTrue => {
             let offset = vm.pop_ref()?;
-            if let Value::Boolean(true) = vm.pop()? {
+            if vm.pop()?.is_true() {
                 return vm.jump(offset);
             } else {
                 vm.step()?;
@@ -32,7 +37,7 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
         }
         Opcode::JumpFalse => {
             let offset = vm.pop_ref()?;
-            if let Value::Boolean(false) = vm.pop()? {
+            if !vm.pop()?.is_true() {
                 return vm.jump(offset);
             } else {
                 vm.step()?;
@@ -56,7 +61,8 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
             let left = vm.pop()?;
             match &[left, right] {
                 [Value::Literal(left_value), Value::Literal(right_value)] => {
-                    vm.push(Value::Literal(left_value + right_value))?;
+                    let result = left_value.checked_add(*right_value);
+                    vm.push(Value::Literal(result.ok_or(ExecuteError::IntegerOverflow)?))?;
                 }
                 _ => {
                     return Err(ExecuteError::InvalidValue);
@@ -68,7 +74,8 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
             let left = vm.pop()?;
             match &[left, right] {
                 [Value::Literal(left_value), Value::Literal(right_value)] => {
-                    vm.push(Value::Literal(left_value - right_value))?;
+                    let result = left_value.checked_sub(*right_value);
+                    vm.push(Value::Literal(result.ok_or(ExecuteError::IntegerOverflow)?))?;
                 }
                 _ => {
                     return Err(ExecuteError::InvalidValue);
@@ -80,7 +87,8 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
             let left = vm.pop()?;
             match &[left, right] {
                 [Value::Literal(left_value), Value::Literal(right_value)] => {
-                    vm.push(Value::Literal(left_value * right_value))?;
+                    let result = left_value.checked_mul(*right_value);
+                    vm.push(Value::Literal(result.ok_or(ExecuteError::IntegerOverflow)?))?;
                 }
                 _ => {
                     return Err(ExecuteError::InvalidValue);
@@ -95,7 +103,8 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
                     return Err(ExecuteError::ZeroDivision);
                 }
                 [Value::Literal(left_value), Value::Literal(right_value)] => {
-                    vm.push(Value::Literal(left_value / right_value))?;
+                    let result = left_value.checked_div(*right_value);
+                    vm.push(Value::Literal(result.ok_or(ExecuteError::IntegerOverflow)?))?;
                 }
                 _ => {
                     return Err(ExecuteError::InvalidValue);
@@ -113,13 +122,37 @@ fn execute_opcode(vm: &mut Vm, opcode: Opcode) -> ExecuteResult<()> {
                 .get_locals()?
                 .get(index)
                 .ok_or(ExecuteError::LocalNotFound(index))?;
-            vm.push(value.clone())?
+            vm.push(*value)?
+        }
+        Opcode::SetLocal(index) => {
+            let value = vm.pop()?;
+            vm.set_local(index, value)?;
         }
         Opcode::Compare => {
             let left = vm.pop()?;
             let right = vm.pop()?;
-            let result = if left == right { true } else { false };
-            vm.push(Value::Boolean(result))?;
+            vm.push(Value::Boolean(left == right))?;
+        }
+        Opcode::CompareLess
+        | Opcode::CompareGreater
+        | Opcode::CompareLessEq
+        | Opcode::CompareGreaterEq => {
+            let right = vm.pop()?;
+            let left = vm.pop()?;
+            match &[left, right] {
+                [Value::Literal(left_value), Value::Literal(right_value)] => {
+                    let result = match opcode {
+                        Opcode::CompareLess => left_value < right_value,
+                        Opcode::CompareGreater => left_value > right_value,
+                        Opcode::CompareLessEq => left_value <= right_value,
+                        _ => left_value >= right_value,
+                    };
+                    vm.push(Value::Boolean(result))?;
+                }
+                _ => {
+                    return Err(ExecuteError::InvalidValue);
+                }
+            }
         }
         Opcode::Noop => {}
         _ => {
@@ -135,3 +168,13 @@ pub fn execute(vm: &mut Vm) -> ExecuteResult<()> {
     let opcode = vm.get_current_opcode()?.clone();
     execute_opcode(vm, opcode)
 }
+
+pub fn run(vm: &mut Vm) -> ExecuteResult<()> {
+    loop {
+        match execute(vm) {
+            Ok(()) => {}
+            Err(ExecuteError::NoOpcode) => return Ok(()),
+            Err(err) => return Err(err),
+        }
+    }
+}
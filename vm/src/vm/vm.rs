@@ -29,9 +29,7 @@ impl Vm {
         let params = self.data_stack.pop_many(num_params)?;
 
         self.call_stack
-            .push(CallStackFrame::new(CodePtr::new(chunk_id, 0), params));
-
-        Ok(())
+            .push(CallStackFrame::new(CodePtr::new(chunk_id, 0), params), &self.context)
     }
 
     pub fn ret(&mut self, num_ret: usize) -> ExecuteResult<()> {
@@ -61,6 +59,10 @@ impl Vm {
         self.data_stack.pop()
     }
 
+    pub fn peek(&self) -> ExecuteResult<Value> {
+        self.data_stack.peek()
+    }
+
     pub fn pop_ref(&mut self) -> ExecuteResult<usize> {
         self.data_stack.pop_ref()
     }
@@ -83,6 +85,11 @@ impl Vm {
         Ok(())
     }
 
+    pub fn set_local(&mut self, index: usize, value: Value) -> ExecuteResult<()> {
+        self.call_stack.peek_mut()?.set_local(index, value);
+        Ok(())
+    }
+
     pub fn get_locals(&self) -> ExecuteResult<&[Value]> {
         self.current_frame().map(|frame| frame.locals())
     }